@@ -0,0 +1,87 @@
+//! Items used by the code generated by `uri-template-ex-macros`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub use std::borrow::Cow;
+pub use std::default::Default;
+pub use std::option::Option::{self, None, Some};
+pub use std::result::Result::{self, Ok};
+pub use std::sync::LazyLock;
+
+use crate::{Captures, DeserializeError, Match, Value};
+
+/// Names of the variables provided by a type that implements `Vars` by `#[derive(Vars)]`.
+pub trait VarNames {
+    const VAR_NAMES: &'static [&'static str];
+}
+pub const fn contains(names: &[&str], name: &str) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        if eq(names[i].as_bytes(), name.as_bytes()) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+const fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+pub fn display_value<T: Display + ?Sized>(value: &T) -> Value<'static> {
+    Value::String(Cow::Owned(value.to_string()))
+}
+
+pub fn into_string(value: Value) -> Option<Cow<str>> {
+    crate::vars::into_string(value)
+}
+
+pub fn field<T>(captures: &Captures, name: &str) -> Result<T, DeserializeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    optional_field(captures, name)?.ok_or_else(|| DeserializeError::missing(name))
+}
+pub fn optional_field<T>(captures: &Captures, name: &str) -> Result<Option<T>, DeserializeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(m) = captures.name(name) else {
+        return Ok(None);
+    };
+    let value = m.value().map_err(|e| DeserializeError::new(name, e))?;
+    parse(m, &value).map(Some)
+}
+pub fn list_field<T>(captures: &Captures, name: &str) -> Result<Vec<T>, DeserializeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(m) = captures.name(name) else {
+        return Ok(Vec::new());
+    };
+    let values = m.values().map_err(|e| DeserializeError::new(name, e))?;
+    values.iter().map(|value| parse(m, value)).collect()
+}
+fn parse<T>(m: &Match, value: &str) -> Result<T, DeserializeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|e| DeserializeError::new(m.name(), format_args!("cannot parse `{value}`: {e}")))
+}
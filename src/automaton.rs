@@ -0,0 +1,303 @@
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// NFA over ASCII bytes built from the regex of a template.
+///
+/// The regexes built by `UriTemplate` only match ASCII strings,
+/// so non-ASCII ranges in character classes are ignored.
+pub(crate) struct Nfa {
+    states: Vec<State>,
+    start: usize,
+    accept: usize,
+}
+
+#[derive(Default)]
+struct State {
+    eps: Vec<usize>,
+    trans: Vec<(u8, u8, usize)>,
+}
+
+impl Nfa {
+    pub fn new(regex: &Regex) -> Self {
+        let hir = regex_syntax::Parser::new().parse(regex.as_str()).unwrap();
+        let mut nfa = Self {
+            states: Vec::new(),
+            start: 0,
+            accept: 0,
+        };
+        let (start, accept) = nfa.build(&hir);
+        nfa.start = start;
+        nfa.accept = accept;
+        nfa
+    }
+    fn push(&mut self) -> usize {
+        self.states.push(State::default());
+        self.states.len() - 1
+    }
+    fn build(&mut self, hir: &Hir) -> (usize, usize) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {
+                let s = self.push();
+                (s, s)
+            }
+            HirKind::Literal(lit) => {
+                let start = self.push();
+                let mut end = start;
+                for &b in lit.0.iter() {
+                    let next = self.push();
+                    self.states[end].trans.push((b, b, next));
+                    end = next;
+                }
+                (start, end)
+            }
+            HirKind::Class(class) => {
+                let start = self.push();
+                let end = self.push();
+                let mut ranges = Vec::new();
+                match class {
+                    Class::Unicode(c) => {
+                        for r in c.iter() {
+                            ranges.push((r.start() as u32, r.end() as u32));
+                        }
+                    }
+                    Class::Bytes(c) => {
+                        for r in c.iter() {
+                            ranges.push((r.start() as u32, r.end() as u32));
+                        }
+                    }
+                }
+                for (lo, hi) in ranges {
+                    if lo <= 0x7F {
+                        let hi = hi.min(0x7F);
+                        self.states[start].trans.push((lo as u8, hi as u8, end));
+                    }
+                }
+                (start, end)
+            }
+            HirKind::Repetition(rep) => {
+                let start = self.push();
+                let mut end = start;
+                for _ in 0..rep.min {
+                    let (s, e) = self.build(&rep.sub);
+                    self.states[end].eps.push(s);
+                    end = e;
+                }
+                match rep.max {
+                    Some(max) => {
+                        let last = self.push();
+                        for _ in rep.min..max {
+                            let (s, e) = self.build(&rep.sub);
+                            self.states[end].eps.push(s);
+                            self.states[end].eps.push(last);
+                            end = e;
+                        }
+                        self.states[end].eps.push(last);
+                        end = last;
+                    }
+                    None => {
+                        let (s, e) = self.build(&rep.sub);
+                        let last = self.push();
+                        self.states[end].eps.push(s);
+                        self.states[end].eps.push(last);
+                        self.states[e].eps.push(s);
+                        self.states[e].eps.push(last);
+                        end = last;
+                    }
+                }
+                (start, end)
+            }
+            HirKind::Capture(cap) => self.build(&cap.sub),
+            HirKind::Concat(subs) => {
+                let start = self.push();
+                let mut end = start;
+                for sub in subs {
+                    let (s, e) = self.build(sub);
+                    self.states[end].eps.push(s);
+                    end = e;
+                }
+                (start, end)
+            }
+            HirKind::Alternation(subs) => {
+                let start = self.push();
+                let end = self.push();
+                for sub in subs {
+                    let (s, e) = self.build(sub);
+                    self.states[start].eps.push(s);
+                    self.states[e].eps.push(end);
+                }
+                (start, end)
+            }
+        }
+    }
+}
+
+/// Returns a string accepted by both automata.
+pub(crate) fn intersection_witness(a: &Nfa, b: &Nfa) -> Option<String> {
+    let start = (a.start, b.start);
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+    parents.insert(start, None);
+    queue.push_back(start);
+    while let Some((sa, sb)) = queue.pop_front() {
+        if sa == a.accept && sb == b.accept {
+            return Some(witness(&parents, (sa, sb)));
+        }
+        let mut nexts = Vec::new();
+        for &na in &a.states[sa].eps {
+            nexts.push(((na, sb), None));
+        }
+        for &nb in &b.states[sb].eps {
+            nexts.push(((sa, nb), None));
+        }
+        for &(lo_a, hi_a, na) in &a.states[sa].trans {
+            for &(lo_b, hi_b, nb) in &b.states[sb].trans {
+                if let Some(c) = pick_byte(lo_a.max(lo_b), hi_a.min(hi_b)) {
+                    nexts.push(((na, nb), Some(c)));
+                }
+            }
+        }
+        for (next, c) in nexts {
+            parents.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                Some(((sa, sb), c))
+            });
+        }
+    }
+    None
+}
+
+/// Returns strings accepted by both automata, shortest first.
+///
+/// Unlike [`intersection_witness`], different strings that reach the same states are distinguished,
+/// so it stops after visiting `limit` strings.
+pub(crate) fn intersection_witnesses<'a>(
+    a: &'a Nfa,
+    b: &'a Nfa,
+    limit: usize,
+) -> impl Iterator<Item = String> + 'a {
+    let classes = byte_classes(&[a, b]);
+    let start = (
+        a.closure(vec![a.start]),
+        b.closure(vec![b.start]),
+        Vec::new(),
+    );
+    let mut queue = VecDeque::from([start]);
+    let mut visited = 1;
+    std::iter::from_fn(move || {
+        while let Some((sa, sb, bytes)) = queue.pop_front() {
+            for &c in &classes {
+                let na = a.step(&sa, c);
+                let nb = b.step(&sb, c);
+                if !na.is_empty() && !nb.is_empty() && visited < limit {
+                    visited += 1;
+                    let mut bytes = bytes.clone();
+                    bytes.push(c);
+                    queue.push_back((na, nb, bytes));
+                }
+            }
+            if sa.contains(&a.accept) && sb.contains(&b.accept) {
+                return Some(String::from_utf8(bytes).unwrap());
+            }
+        }
+        None
+    })
+}
+
+type Parents<S> = HashMap<S, Option<(S, Option<u8>)>>;
+
+fn witness<S: Clone + Eq + Hash>(parents: &Parents<S>, mut state: S) -> String {
+    let mut bytes = Vec::new();
+    while let Some(Some((parent, c))) = parents.get(&state) {
+        bytes.extend(c);
+        state = parent.clone();
+    }
+    bytes.reverse();
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Picks a readable byte from `lo..=hi`.
+fn pick_byte(lo: u8, hi: u8) -> Option<u8> {
+    if lo > hi {
+        return None;
+    }
+    b"abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-._~"
+        .iter()
+        .copied()
+        .find(|c| (lo..=hi).contains(c))
+        .or(Some(lo))
+}
+
+/// Returns a string accepted by `a` but not by `b`.
+pub(crate) fn difference_witness(a: &Nfa, b: &Nfa) -> Option<String> {
+    let classes = byte_classes(&[a, b]);
+    let start = (a.closure(vec![a.start]), b.closure(vec![b.start]));
+    let mut parents: Parents<(Vec<usize>, Vec<usize>)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    parents.insert(start.clone(), None);
+    queue.push_back(start);
+    while let Some((sa, sb)) = queue.pop_front() {
+        if sa.contains(&a.accept) && !sb.contains(&b.accept) {
+            return Some(witness(&parents, (sa, sb)));
+        }
+        for &c in &classes {
+            let na = a.step(&sa, c);
+            if na.is_empty() {
+                continue;
+            }
+            let next = (na, b.step(&sb, c));
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(((sa.clone(), sb.clone()), Some(c))));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+impl Nfa {
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            for &next in &self.states[states[i]].eps {
+                if !states.contains(&next) {
+                    states.push(next);
+                }
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states
+    }
+    fn step(&self, states: &[usize], c: u8) -> Vec<usize> {
+        let mut nexts = Vec::new();
+        for &s in states {
+            for &(lo, hi, next) in &self.states[s].trans {
+                if lo <= c && c <= hi && !nexts.contains(&next) {
+                    nexts.push(next);
+                }
+            }
+        }
+        self.closure(nexts)
+    }
+}
+
+/// Returns one readable byte for each set of bytes that no automaton distinguishes.
+fn byte_classes(nfas: &[&Nfa]) -> Vec<u8> {
+    let mut bounds = vec![0, 0x80];
+    for nfa in nfas {
+        for s in &nfa.states {
+            for &(lo, hi, _) in &s.trans {
+                bounds.push(lo as u16);
+                bounds.push(hi as u16 + 1);
+            }
+        }
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds
+        .windows(2)
+        .filter_map(|w| pick_byte(w[0] as u8, (w[1] - 1) as u8))
+        .collect()
+}
@@ -0,0 +1,302 @@
+use serde::Deserialize;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::{Captures, Match};
+
+/// Error returned by [`Captures::deserialize`] and [`FromCaptures::from_captures`].
+#[derive(Clone, Debug)]
+pub struct DeserializeError {
+    var: Option<String>,
+    message: String,
+}
+
+impl DeserializeError {
+    /// Returns the name of the variable that could not be deserialized.
+    pub fn var(&self) -> Option<&str> {
+        self.var.as_deref()
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub(crate) fn new(var: &str, message: impl fmt::Display) -> Self {
+        Self {
+            var: Some(var.to_string()),
+            message: message.to_string(),
+        }
+    }
+    pub(crate) fn missing(var: &str) -> Self {
+        Self::new(var, format_args!("missing field `{var}`"))
+    }
+    fn with_var(mut self, var: &str) -> Self {
+        self.var.get_or_insert_with(|| var.to_string());
+        self
+    }
+}
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            var: None,
+            message: msg.to_string(),
+        }
+    }
+    fn missing_field(field: &'static str) -> Self {
+        Self::missing(field)
+    }
+}
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.var {
+            Some(var) => write!(f, "variable `{var}`: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+impl std::error::Error for DeserializeError {}
+
+type Result<T> = std::result::Result<T, DeserializeError>;
+
+impl<'a> Captures<'a> {
+    /// Deserializes the captured variables into `T`.
+    ///
+    /// Each variable is deserialized from its decoded value.
+    /// Numbers and booleans are parsed from the text,
+    /// and variables that did not match are treated as missing fields.
+    pub fn deserialize<'de, T: Deserialize<'de>>(&'de self) -> Result<T> {
+        T::deserialize(CapturesDeserializer(self))
+    }
+}
+
+/// Types that can be created from captured variables.
+///
+/// With the `derive` feature, this trait can be implemented by `#[derive(FromCaptures)]`.
+pub trait FromCaptures: Sized {
+    fn from_captures(captures: &Captures) -> Result<Self>;
+}
+
+struct CapturesDeserializer<'de>(&'de Captures<'de>);
+
+impl<'de> de::Deserializer<'de> for CapturesDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut ms: Vec<&Match> = Vec::new();
+        for (name, _) in self.0.iter() {
+            if ms.iter().all(|m| m.name() != name)
+                && let Some(m) = self.0.name(name)
+            {
+                ms.push(m);
+            }
+        }
+        visitor.visit_map(CapturesMapAccess {
+            ms: ms.into_iter(),
+            value: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct CapturesMapAccess<'de, I> {
+    ms: I,
+    value: Option<&'de Match<'de>>,
+}
+
+impl<'de, I: Iterator<Item = &'de Match<'de>>> MapAccess<'de> for CapturesMapAccess<'de, I> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some(m) = self.ms.next() else {
+            return Ok(None);
+        };
+        self.value = Some(m);
+        seed.deserialize(m.name().into_deserializer()).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let m = self.value.take().unwrap();
+        seed.deserialize(MatchDeserializer(m))
+            .map_err(|e| e.with_var(m.name()))
+    }
+}
+
+struct MatchDeserializer<'de>(&'de Match<'de>);
+
+impl<'de> MatchDeserializer<'de> {
+    fn value(&self) -> Result<ValueDeserializer<'de>> {
+        self.0
+            .value()
+            .map(ValueDeserializer)
+            .map_err(de::Error::custom)
+    }
+}
+
+macro_rules! deserialize_value {
+    ($($deserialize:ident,)*) => {
+        $(
+            fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                self.value()?.$deserialize(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MatchDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.value()?.deserialize_any(visitor)
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let values = self.0.values().map_err(de::Error::custom)?;
+        let mut seq = SeqDeserializer::new(values.into_iter().map(ValueDeserializer));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let pairs = self.0.pairs().map_err(de::Error::custom)?;
+        let mut map = MapDeserializer::new(
+            pairs
+                .into_iter()
+                .map(|(k, v)| (ValueDeserializer(k), ValueDeserializer(v))),
+        );
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.value()?.deserialize_enum(name, variants, visitor)
+    }
+    deserialize_value! {
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_unit,
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct tuple_struct struct identifier ignored_any
+    }
+}
+
+/// Deserializer for a decoded value that parses numbers and booleans from the text.
+struct ValueDeserializer<'de>(Cow<'de, str>);
+
+impl ValueDeserializer<'_> {
+    fn parse<T: std::str::FromStr>(&self) -> Result<T>
+    where
+        T::Err: fmt::Display,
+    {
+        self.0
+            .parse()
+            .map_err(|e| de::Error::custom(format_args!("cannot parse `{}`: {e}", self.0)))
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($deserialize:ident => $visit:ident,)*) => {
+        $(
+            fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
+    }
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
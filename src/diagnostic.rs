@@ -0,0 +1,127 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::{Error, ErrorKind, encode_char, is_var_name};
+
+/// Problem found by [`UriTemplate::parse_with_diagnostics`](crate::UriTemplate::parse_with_diagnostics).
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    kind: ErrorKind,
+    span: Range<usize>,
+    message: String,
+    suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(e: &Error) -> Self {
+        let text = &e.source[e.span.clone()];
+        let (message, suggestion) = match e.kind {
+            ErrorKind::UnclosedExpression => (
+                format!("expression `{text}` is not closed"),
+                Some("add `}` at the end of the expression".to_string()),
+            ),
+            ErrorKind::EmptyExpression => (
+                format!("expression `{text}` has no variables"),
+                Some("add a variable name, or percent-encode the braces as `%7B%7D`".to_string()),
+            ),
+            ErrorKind::UnexpectedCloseBrace => (
+                "`}` without a matching `{`".to_string(),
+                Some("percent-encode it as `%7D`".to_string()),
+            ),
+            ErrorKind::NestedBrace => (
+                "`{` inside an expression".to_string(),
+                Some("close the previous expression with `}`".to_string()),
+            ),
+            ErrorKind::UnknownOperator => (
+                format!("operator `{text}` is reserved for future extensions"),
+                Some("use one of `+`, `#`, `.`, `/`, `;`, `?` and `&`".to_string()),
+            ),
+            ErrorKind::InvalidVarName if text.is_empty() => (
+                "variable name is empty".to_string(),
+                Some("remove the extra `,`".to_string()),
+            ),
+            ErrorKind::InvalidVarName => (
+                format!("invalid variable name `{text}`"),
+                Some(
+                    suggest_var_name(text)
+                        .map_or_else(|| VAR_NAME_HINT.to_string(), |name| format!("use `{name}`")),
+                ),
+            ),
+            ErrorKind::InvalidModifier if text.starts_with(':') && text.ends_with('*') => (
+                format!("invalid modifier `{text}`"),
+                Some("prefix and explode modifiers cannot be combined".to_string()),
+            ),
+            ErrorKind::InvalidModifier => (
+                format!("invalid modifier `{text}`"),
+                Some("the prefix length must be an integer from 1 to 9999".to_string()),
+            ),
+            ErrorKind::InvalidLiteral => {
+                let mut encoded = String::new();
+                for ch in text.chars() {
+                    encode_char(ch, &mut encoded);
+                }
+                (
+                    format!(
+                        "character `{}` is not allowed in literals",
+                        text.escape_debug()
+                    ),
+                    Some(format!("percent-encode it as `{encoded}`")),
+                )
+            }
+            ErrorKind::InvalidPercentEncoding => (
+                "`%` is not followed by two hexadecimal digits".to_string(),
+                Some("percent-encode it as `%25`".to_string()),
+            ),
+            ErrorKind::InvalidUtf8 => (format!("`{text}` is not valid UTF-8"), None),
+            ErrorKind::PrefixOnCompositeValue => (
+                format!(
+                    "prefix modifier `{text}` cannot be applied to a list or an associative array"
+                ),
+                None,
+            ),
+            ErrorKind::TooComplex => (
+                "template is too large to be matched".to_string(),
+                Some("split the expressions with many variables".to_string()),
+            ),
+        };
+        Self {
+            kind: e.kind,
+            span: e.span.clone(),
+            message,
+            suggestion,
+        }
+    }
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+const VAR_NAME_HINT: &str =
+    "variable names can contain only letters, digits, `_`, `.` and percent-encoded characters";
+
+/// Percent-encodes the characters that are not allowed in variable names.
+fn suggest_var_name(name: &str) -> Option<String> {
+    let mut s = String::new();
+    for ch in name.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '_' || ch == '.' || ch == '%' {
+            s.push(ch);
+        } else {
+            encode_char(ch, &mut s);
+        }
+    }
+    is_var_name(&s).then_some(s)
+}
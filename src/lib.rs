@@ -1,24 +1,48 @@
 use parse_display::Display;
 use regex::{Regex, escape};
+use std::cmp::{Ordering, Reverse};
 use std::fmt::Write;
 use std::ops::Range;
 use std::str::{self, CharIndices};
-use std::sync::LazyLock;
+use std::sync::{LazyLock, OnceLock};
 use std::{borrow::Cow, fmt};
 
-mod vars;
+#[doc(hidden)]
+pub mod __private;
+mod automaton;
+mod de;
+mod diagnostic;
+mod lint;
+mod owned;
+mod rewrite;
+mod router;
+pub mod vars;
 
 mod tests_readme;
 
-pub use vars::Vars;
+pub use de::{DeserializeError, FromCaptures};
+pub use diagnostic::Diagnostic;
+pub use lint::{Lint, LintKind};
+pub use owned::{OwnedCaptures, OwnedMatch};
+pub use rewrite::{Rewrite, RewriteRule, RewriteRules, SkipReason};
+pub use router::Router;
+pub use vars::{ToValue, Value, Vars};
 
-/// RFC6570 Level 2
+#[cfg(feature = "macros")]
+pub use uri_template_ex_macros::uri_template;
+#[cfg(feature = "derive")]
+pub use uri_template_ex_macros::{FromCaptures, Vars};
+
+/// RFC6570 Level 4
 #[derive(Clone)]
 pub struct UriTemplate {
     source: String,
     segments: Vec<Segment>,
     exprs: Vec<Expr>,
+    vars: Vec<VarSpec>,
     regex: Regex,
+    prefix_regex: OnceLock<Regex>,
+    split_regexes: OnceLock<SplitRegexes>,
 }
 impl std::fmt::Debug for UriTemplate {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -38,14 +62,17 @@ enum Segment {
     Expr,
 }
 impl Segment {
+    #[allow(clippy::too_many_arguments)]
     fn expand(
         &self,
         source: &str,
         source_index: &mut usize,
         exprs: &[Expr],
         expr_index: &mut usize,
+        var_specs: &[VarSpec],
         vars: &mut impl Vars,
         out: &mut String,
+        error: &mut Option<Error>,
     ) {
         match self {
             Segment::Literals { len } => {
@@ -60,8 +87,8 @@ impl Segment {
             }
             Segment::Expr => {
                 let expr = &exprs[*expr_index];
-                expr.expand(source, *expr_index, vars, out);
-                *source_index += expr.len();
+                expr.expand(source, var_specs, vars, out, error);
+                *source_index += expr.len;
                 *expr_index += 1;
             }
         }
@@ -71,37 +98,278 @@ impl Segment {
 #[derive(Debug, Clone)]
 struct Expr {
     op: Option<Operator>,
-    var_name_range: Range<usize>,
+    len: usize,
+    vars: Range<usize>,
+    regex_range: Range<usize>,
 }
 impl Expr {
-    fn len(&self) -> usize {
-        self.var_name_range.len() + 2 + if self.op.is_some() { 1 } else { 0 }
-    }
-    fn to_regex(&self) -> String {
-        match self.op {
-            Some(op) => {
-                let prefix = escape(op.to_prefix());
-                format!("(?:{prefix}([{RE_UNRESERVED}{RE_RESERVED}%]*))?",)
+    fn first(&self) -> &'static str {
+        self.op.map_or("", |op| op.first())
+    }
+    fn sep(&self) -> &'static str {
+        self.op.map_or(",", |op| op.sep())
+    }
+    fn named(&self) -> bool {
+        self.op.is_some_and(|op| op.named())
+    }
+    fn ifemp(&self) -> &'static str {
+        self.op.map_or("", |op| op.ifemp())
+    }
+    fn allow_reserved(&self) -> bool {
+        self.op.is_some_and(|op| op.allow_reserved())
+    }
+    fn to_regex(&self, source: &str, var_specs: &mut [VarSpec], group_count: &mut usize) -> String {
+        let mut re = format!("(?:{}", escape(self.first()));
+        self.vars_regex(
+            source,
+            &mut var_specs[self.vars.clone()],
+            group_count,
+            &mut re,
+        );
+        re.push_str(")?");
+        re
+    }
+
+    /// Writes the regex that matches one or more of the variables in order, joined by the separator.
+    ///
+    /// Either some of the first half of the variables are present, followed by any of the second half,
+    /// or only some of the second half are present.
+    /// Splitting in halves keeps the size of the regex at O(n log n) and its nesting at O(log n).
+    fn vars_regex(
+        &self,
+        source: &str,
+        var_specs: &mut [VarSpec],
+        group_count: &mut usize,
+        re: &mut String,
+    ) {
+        if let [var] = var_specs {
+            let value = self.var_regex(var, &source[var.name_range.clone()]);
+            *group_count += 1;
+            var.groups.push(*group_count);
+            write!(re, "({value})").unwrap();
+            return;
+        }
+        let sep = escape(self.sep());
+        let (head, tail) = var_specs.split_at_mut(var_specs.len() / 2);
+        re.push_str("(?:");
+        self.vars_regex(source, head, group_count, re);
+        for var in tail.iter_mut() {
+            let value = self.var_regex(var, &source[var.name_range.clone()]);
+            *group_count += 1;
+            var.groups.push(*group_count);
+            write!(re, "(?:{sep}({value}))?").unwrap();
+        }
+        re.push('|');
+        self.vars_regex(source, tail, group_count, re);
+        re.push(')');
+    }
+    fn var_regex(&self, var: &VarSpec, var_name: &str) -> String {
+        let sep = escape(self.sep());
+        let mut class = String::from(RE_UNRESERVED);
+        if self.allow_reserved() {
+            class.push_str(RE_RESERVED);
+        }
+        class.push('%');
+        if var.modifier == Some(Modifier::Explode) {
+            let item = if self.named() {
+                format!("[{class}]+(?:=[{class}]*)?")
+            } else {
+                format!("[{class}=--{sep}]*")
+            };
+            return format!("{item}(?:{sep}{item})*");
+        }
+        if !self.allow_reserved() {
+            class.push(',');
+        }
+        if self.vars.len() > 1 {
+            class = format!("{class}--{sep}");
+        }
+        // The length of a prefix modifier is checked after matching by `is_consistent`.
+        let value = format!("[{class}]*");
+        if self.named() {
+            format!("{}(?:={value})?", escape(var_name))
+        } else {
+            value
+        }
+    }
+    fn expand(
+        &self,
+        source: &str,
+        var_specs: &[VarSpec],
+        vars: &mut impl Vars,
+        out: &mut String,
+        error: &mut Option<Error>,
+    ) {
+        let mut first = true;
+        for var_index in self.vars.clone() {
+            let var = &var_specs[var_index];
+            let var_name = &source[var.name_range.clone()];
+            let encoded = !self.allow_reserved() && vars.is_encoded(var_index, var_name);
+            let Some(mut value) = vars.value(var_index, var_name) else {
+                continue;
+            };
+            if encoded {
+                value = decode_value(value);
+            }
+            if value.is_undefined() {
+                continue;
+            }
+            out.push_str(if first { self.first() } else { self.sep() });
+            first = false;
+            let explode = var.modifier == Some(Modifier::Explode);
+            match value {
+                Value::String(value) => {
+                    let value = match var.modifier {
+                        Some(Modifier::Prefix(max_len)) => truncate_chars(&value, max_len),
+                        _ => &value,
+                    };
+                    self.expand_named(var_name, value.is_empty(), out);
+                    self.encode(value, out);
+                }
+                Value::List(items) => {
+                    self.check_not_prefix(source, var, error);
+                    if explode {
+                        for (i, item) in items.iter().enumerate() {
+                            if i > 0 {
+                                out.push_str(self.sep());
+                            }
+                            self.expand_named(var_name, item.is_empty(), out);
+                            self.encode(item, out);
+                        }
+                    } else {
+                        self.expand_named(var_name, false, out);
+                        for (i, item) in items.iter().enumerate() {
+                            if i > 0 {
+                                out.push(',');
+                            }
+                            self.encode(item, out);
+                        }
+                    }
+                }
+                Value::AssocArray(items) => {
+                    self.check_not_prefix(source, var, error);
+                    if explode {
+                        for (i, (key, value)) in items.iter().enumerate() {
+                            if i > 0 {
+                                out.push_str(self.sep());
+                            }
+                            self.encode(key, out);
+                            if self.named() && value.is_empty() {
+                                out.push_str(self.ifemp());
+                            } else {
+                                out.push('=');
+                                self.encode(value, out);
+                            }
+                        }
+                    } else {
+                        self.expand_named(var_name, false, out);
+                        for (i, (key, value)) in items.iter().enumerate() {
+                            if i > 0 {
+                                out.push(',');
+                            }
+                            self.encode(key, out);
+                            out.push(',');
+                            self.encode(value, out);
+                        }
+                    }
+                }
             }
-            None => format!("([{RE_UNRESERVED}%]*)",),
         }
     }
-    fn expand(&self, source: &str, expr_index: usize, vars: &mut impl Vars, out: &mut String) {
-        let var_name = &source[self.var_name_range.clone()];
-        let var = vars.var(expr_index, var_name);
-        let Some(var) = var else {
-            return;
+    fn expand_named(&self, var_name: &str, is_empty: bool, out: &mut String) {
+        if self.named() {
+            out.push_str(var_name);
+            out.push_str(if is_empty { self.ifemp() } else { "=" });
+        }
+    }
+    fn check_not_prefix(&self, source: &str, var: &VarSpec, error: &mut Option<Error>) {
+        if let Some(Modifier::Prefix(_)) = var.modifier {
+            error.get_or_insert_with(|| {
+                Error::new(
+                    source,
+                    var.name_range.end..var.range.end,
+                    ErrorKind::PrefixOnCompositeValue,
+                )
+            });
+        }
+    }
+    fn encode(&self, s: &str, out: &mut String) {
+        if self.allow_reserved() {
+            encode_str_url(s, out);
+        } else {
+            encode_str_unresreved(s, out);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VarSpec {
+    range: Range<usize>,
+    name_range: Range<usize>,
+    modifier: Option<Modifier>,
+    expr_index: usize,
+    groups: Vec<usize>,
+}
+impl VarSpec {
+    fn parse(source: &str, range: Range<usize>, expr_index: usize) -> Result<Self> {
+        let s = &source[range.clone()];
+        let (name_len, modifier) = if let Some(i) = s.find(':') {
+            let Some(max_len) = parse_max_length(&s[i + 1..]) else {
+                return Err(Error::new(
+                    source,
+                    range.start + i..range.end,
+                    ErrorKind::InvalidModifier,
+                ));
+            };
+            (i, Some(Modifier::Prefix(max_len)))
+        } else if let Some(name) = s.strip_suffix('*') {
+            (name.len(), Some(Modifier::Explode))
+        } else {
+            (s.len(), None)
         };
-        match self.op {
-            Some(op) => {
-                out.push_str(op.to_prefix());
-                encode_str_url(&var, out);
-            }
-            None => {
-                encode_str_unresreved(&var, out);
+        let name_range = range.start..range.start + name_len;
+        if !is_var_name(&source[name_range.clone()]) {
+            return Err(Error::new(source, name_range, ErrorKind::InvalidVarName));
+        }
+        Ok(Self {
+            range,
+            name_range,
+            modifier,
+            expr_index,
+            groups: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Modifier {
+    /// `:max-length`
+    Prefix(usize),
+    /// `*`
+    Explode,
+}
+
+/// `varname = varchar *( ["."] varchar )`
+fn is_var_name(s: &str) -> bool {
+    let mut after_dot = true;
+    for d in DecodedIter::new(s) {
+        match d {
+            Decoded::Char { ch: '.', .. } if !after_dot => after_dot = true,
+            Decoded::Char { ch, .. } if ch.is_ascii_alphanumeric() || ch == '_' => {
+                after_dot = false
             }
+            Decoded::Char { .. } => return false,
+            Decoded::Byte { .. } => after_dot = false,
         }
     }
+    !after_dot
+}
+
+fn parse_max_length(s: &str) -> Option<usize> {
+    if !(1..=4).contains(&s.len()) || s.starts_with('0') || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -110,27 +378,98 @@ enum Operator {
     Reserved,
     /// `#`
     Fragment,
+    /// `.`
+    Label,
+    /// `/`
+    PathSegment,
+    /// `;`
+    PathParameter,
+    /// `?`
+    Query,
+    /// `&`
+    QueryContinuation,
 }
 impl Operator {
     fn from_char(c: char) -> Option<Self> {
         match c {
             '+' => Some(Self::Reserved),
             '#' => Some(Self::Fragment),
+            '.' => Some(Self::Label),
+            '/' => Some(Self::PathSegment),
+            ';' => Some(Self::PathParameter),
+            '?' => Some(Self::Query),
+            '&' => Some(Self::QueryContinuation),
             _ => None,
         }
     }
-    fn to_prefix(self) -> &'static str {
+    fn to_char(self) -> char {
+        match self {
+            Self::Reserved => '+',
+            Self::Fragment => '#',
+            Self::Label => '.',
+            Self::PathSegment => '/',
+            Self::PathParameter => ';',
+            Self::Query => '?',
+            Self::QueryContinuation => '&',
+        }
+    }
+    fn first(self) -> &'static str {
         match self {
             Self::Reserved => "",
             Self::Fragment => "#",
+            Self::Label => ".",
+            Self::PathSegment => "/",
+            Self::PathParameter => ";",
+            Self::Query => "?",
+            Self::QueryContinuation => "&",
+        }
+    }
+    fn sep(self) -> &'static str {
+        match self {
+            Self::Reserved | Self::Fragment => ",",
+            Self::Label => ".",
+            Self::PathSegment => "/",
+            Self::PathParameter => ";",
+            Self::Query | Self::QueryContinuation => "&",
         }
     }
+    fn named(self) -> bool {
+        matches!(
+            self,
+            Self::PathParameter | Self::Query | Self::QueryContinuation
+        )
+    }
+    fn ifemp(self) -> &'static str {
+        match self {
+            Self::Query | Self::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+    fn allow_reserved(self) -> bool {
+        matches!(self, Self::Reserved | Self::Fragment)
+    }
 }
 
 impl UriTemplate {
     pub fn new(s: &str) -> Result<Self> {
+        Self::parse(s, false).map_err(|mut errors| errors.swap_remove(0))
+    }
+
+    /// Parses a template strictly according to the grammar of RFC6570.
+    ///
+    /// In addition to the checks performed by [`UriTemplate::new`],
+    /// characters that are not allowed in literals (such as space, `<`, `>`, `"`, `^` and `|`)
+    /// and `%` not followed by two hexadecimal digits are rejected instead of being percent-encoded.
+    pub fn new_strict(s: &str) -> Result<Self> {
+        Self::parse(s, true).map_err(|mut errors| errors.swap_remove(0))
+    }
+
+    fn parse(s: &str, strict: bool) -> std::result::Result<Self, Vec<Error>> {
+        let mut errors = Vec::new();
         let mut segments = Vec::new();
         let mut exprs = Vec::new();
+        let mut vars = Vec::new();
+        let mut group_count = 0;
         let mut iter = DecodedIter::new(s);
         let mut current = iter.next();
         let mut re = String::from("^");
@@ -140,37 +479,110 @@ impl UriTemplate {
                 Decoded::Char { index, ch: '{' } => {
                     let var_start = index;
                     let mut op = None;
+                    let mut is_valid = true;
                     current = iter.next();
-                    if let Some(d) = current {
-                        if let Some(ch) = d.ch() {
-                            op = Operator::from_char(ch);
-                        }
+                    if let Some(d) = current
+                        && let Some(ch) = d.ch()
+                    {
+                        op = Operator::from_char(ch);
                         if op.is_some() {
                             current = iter.next();
+                        } else if is_op_reserve(ch) {
+                            errors.push(Error::new(
+                                s,
+                                d.index()..d.index() + 1,
+                                ErrorKind::UnknownOperator,
+                            ));
+                            is_valid = false;
+                            current = iter.next();
                         }
                     }
-                    if let Some(d) = current {
-                        let var_name_start = d.index();
-                        while let Some(d) = current {
-                            if d.ch() == Some('}') {
-                                let expr = Expr {
+                    let vars_start = vars.len();
+                    let body_start = current.map_or(s.len(), |d| d.index());
+                    let mut var_name_start = body_start;
+                    let mut is_nested = false;
+                    while let Some(d) = current {
+                        match d.ch() {
+                            Some('{') if !is_nested => {
+                                errors.push(Error::new(
+                                    s,
+                                    d.index()..d.index() + 1,
+                                    ErrorKind::NestedBrace,
+                                ));
+                                is_nested = true;
+                            }
+                            Some(',' | '}') if !is_nested => {
+                                if d.index() == body_start && d.ch() == Some('}') {
+                                    if is_valid {
+                                        errors.push(Error::new(
+                                            s,
+                                            var_start..d.index() + 1,
+                                            ErrorKind::EmptyExpression,
+                                        ));
+                                    }
+                                    is_valid = false;
+                                } else {
+                                    let range = var_name_start..d.index();
+                                    match VarSpec::parse(s, range, exprs.len()) {
+                                        Ok(var) => vars.push(var),
+                                        Err(e) => {
+                                            errors.push(e);
+                                            is_valid = false;
+                                        }
+                                    }
+                                }
+                                var_name_start = d.index() + 1;
+                            }
+                            _ => {}
+                        }
+                        if d.ch() == Some('}') {
+                            if is_valid && !is_nested {
+                                let mut expr = Expr {
                                     op,
-                                    var_name_range: var_name_start..d.index(),
+                                    len: d.index() + 1 - var_start,
+                                    vars: vars_start..vars.len(),
+                                    regex_range: 0..0,
                                 };
-                                re.push_str(&expr.to_regex());
+                                let regex_start = re.len();
+                                re.push_str(&expr.to_regex(s, &mut vars, &mut group_count));
+                                expr.regex_range = regex_start..re.len();
                                 exprs.push(expr);
                                 segments.push(Segment::Expr);
-                                current = iter.next();
-                                continue 'root;
+                            } else {
+                                vars.truncate(vars_start);
                             }
                             current = iter.next();
+                            continue 'root;
                         }
-                        return Err(Error {
-                            source: s.to_string(),
-                            kind: ErrorKind::InvalidExpression,
-                            source_index: var_start,
-                        });
+                        current = iter.next();
                     }
+                    errors.push(Error::new(
+                        s,
+                        var_start..s.len(),
+                        ErrorKind::UnclosedExpression,
+                    ));
+                    break;
+                }
+                Decoded::Char { index, ch: '}' } => {
+                    errors.push(Error::new(
+                        s,
+                        index..index + 1,
+                        ErrorKind::UnexpectedCloseBrace,
+                    ));
+                }
+                Decoded::Char { index, ch: '%' } if strict => {
+                    errors.push(Error::new(
+                        s,
+                        index..index + 1,
+                        ErrorKind::InvalidPercentEncoding,
+                    ));
+                }
+                Decoded::Char { index, ch } if strict && !is_literal(ch) => {
+                    errors.push(Error::new(
+                        s,
+                        index..index + ch.len_utf8(),
+                        ErrorKind::InvalidLiteral,
+                    ));
                 }
                 Decoded::Char { ch, .. } => {
                     let len = ch.len_utf8();
@@ -191,17 +603,47 @@ impl UriTemplate {
             }
             current = iter.next();
         }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
         re.push('$');
+        let Ok(regex) = Regex::new(&re) else {
+            return Err(vec![Error::new(s, 0..s.len(), ErrorKind::TooComplex)]);
+        };
         Ok(Self {
             source: s.to_string(),
             segments,
             exprs,
-            regex: Regex::new(&re).unwrap(),
+            vars,
+            regex,
+            prefix_regex: OnceLock::new(),
+            split_regexes: OnceLock::new(),
         })
     }
 
-    pub fn expand(&self, mut vars: impl Vars) -> String {
+    /// Parses a template, reporting all errors instead of stopping at the first one.
+    ///
+    /// After an invalid expression, parsing resumes at the next `}`.
+    pub fn parse_with_diagnostics(s: &str) -> std::result::Result<Self, Vec<Diagnostic>> {
+        Self::parse(s, false).map_err(|errors| errors.iter().map(Diagnostic::new).collect())
+    }
+
+    pub fn expand(&self, vars: impl Vars) -> String {
+        self.expand_with_error(vars).0
+    }
+
+    /// Expands the template, returning an error if a variable value cannot be expanded.
+    ///
+    /// For example, a prefix modifier applied to a list or an associative array is an error.
+    pub fn try_expand(&self, vars: impl Vars) -> Result<String> {
+        match self.expand_with_error(vars) {
+            (out, None) => Ok(out),
+            (_, Some(e)) => Err(e),
+        }
+    }
+    fn expand_with_error(&self, mut vars: impl Vars) -> (String, Option<Error>) {
         let mut out = String::new();
+        let mut error = None;
         let mut expr_index = 0;
         let mut source_index = 0;
         for segment in &self.segments {
@@ -210,36 +652,337 @@ impl UriTemplate {
                 &mut source_index,
                 &self.exprs,
                 &mut expr_index,
+                &self.vars,
                 &mut vars,
                 &mut out,
+                &mut error,
             );
         }
-        out
+        (out, error)
     }
+    /// Returns the values of the variables if `input` matches the template.
+    ///
+    /// If a variable appears more than once, all occurrences must have the same value,
+    /// and occurrences with a prefix modifier must have the prefix of the value.
+    /// When the first way of splitting `input` into variables does not satisfy this,
+    /// other ways are tried, up to a limit on the length of input scanned.
     pub fn captures<'a>(&'a self, input: &'a str) -> Option<Captures<'a>> {
         let captures = self.regex.captures(input)?;
-        let mut ms = Vec::with_capacity(self.exprs.len());
-        for (expr_index, expr) in self.exprs.iter().enumerate() {
-            if let Some(m) = captures.get(expr_index + 1) {
-                ms.push(Some(Match::new(m, self.var_name(expr_index), expr.op)));
+        let ms = self.to_matches(input, self.spans(&captures));
+        if is_consistent(&ms) {
+            return Some(Captures { template: self, ms });
+        }
+        let mut spans = vec![None; self.vars.len()];
+        let mut budget = MAX_SPLIT_SCAN_LEN;
+        if self.split(input, 0, 0, &mut spans, &mut budget) {
+            let ms = self.to_matches(input, spans);
+            return Some(Captures { template: self, ms });
+        }
+        None
+    }
+
+    /// Matches the template against the beginning of `input`
+    /// and returns the captures and the rest of `input`.
+    ///
+    /// The rest is empty or starts with `/`, `?` or `#`,
+    /// unless the template ends with `/`.
+    /// If the template ends with `{?var}` or `{&var}` and the matched part contains the `?` that starts the query,
+    /// the rest can also start with `&`, so that query parameters not in the template are left in the rest.
+    ///
+    /// ```
+    /// use uri_template_ex::UriTemplate;
+    ///
+    /// let template = UriTemplate::new("/tenants/{tenant}")?;
+    /// let (captures, rest) = template.captures_prefix("/tenants/acme/users/1").unwrap();
+    /// assert_eq!(captures.name("tenant").unwrap().value()?, "acme");
+    /// assert_eq!(rest, "/users/1");
+    /// assert!(template.captures_prefix("/tenantsx").is_none());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn captures_prefix<'a>(&'a self, input: &'a str) -> Option<(Captures<'a>, &'a str)> {
+        let regex = self.prefix_regex.get_or_init(|| {
+            let re = self.regex.as_str().strip_suffix('$').unwrap();
+            let rest = if self.source.ends_with('/') {
+                "(?s:.*)".to_string()
             } else {
-                ms.push(None);
+                format!("(?s:[{}].*)?", escape(self.prefix_rest_starts()))
+            };
+            Regex::new(&format!("{re}({rest})$")).unwrap()
+        });
+        let captures = regex.captures(input)?;
+        let rest = captures.get(captures.len() - 1).unwrap();
+        let ms = self.to_matches(input, self.spans(&captures));
+        if is_consistent(&ms) && self.is_prefix_rest(input, rest.start()) {
+            return Some((Captures { template: self, ms }, rest.as_str()));
+        }
+        (0..=rest.start())
+            .rev()
+            .filter(|&end| input.is_char_boundary(end) && self.is_prefix_rest(input, end))
+            .find_map(|end| Some((self.captures(&input[..end])?, &input[end..])))
+    }
+    /// Returns `true` if `input[end..]` can be the rest of [`UriTemplate::captures_prefix`].
+    fn is_prefix_rest(&self, input: &str, end: usize) -> bool {
+        match input[end..].chars().next() {
+            None => true,
+            Some(_) if self.source.ends_with('/') => true,
+            // `&` continues the query only after the query has started.
+            Some('&') => self.prefix_rest_starts().contains('&') && input[..end].contains('?'),
+            Some(c) => self.prefix_rest_starts().contains(c),
+        }
+    }
+    /// Returns the characters that can start the rest of [`UriTemplate::captures_prefix`].
+    fn prefix_rest_starts(&self) -> &'static str {
+        let ends_with_query = matches!(self.segments.last(), Some(Segment::Expr))
+            && self.exprs.last().is_some_and(|expr| {
+                matches!(expr.op, Some(Operator::Query | Operator::QueryContinuation))
+            });
+        if ends_with_query { "/?#&" } else { "/?#" }
+    }
+    fn spans(&self, captures: &regex::Captures) -> Vec<Option<Range<usize>>> {
+        self.vars
+            .iter()
+            .map(|var| {
+                let m = var.groups.iter().find_map(|&group| captures.get(group))?;
+                Some(m.range())
+            })
+            .collect()
+    }
+    fn to_matches<'a>(
+        &'a self,
+        input: &'a str,
+        spans: Vec<Option<Range<usize>>>,
+    ) -> Vec<Option<Match<'a>>> {
+        let mut ms = Vec::with_capacity(self.vars.len());
+        for (var_index, span) in spans.into_iter().enumerate() {
+            ms.push(span.map(|span| {
+                let var = &self.vars[var_index];
+                let expr = &self.exprs[var.expr_index];
+                let name = self.var_name(var_index);
+                let mut start = span.start;
+                if expr.named() && var.modifier != Some(Modifier::Explode) {
+                    start += name.len();
+                    if input[start..span.end].starts_with('=') {
+                        start += 1;
+                    }
+                }
+                let max_len = match var.modifier {
+                    Some(Modifier::Prefix(max_len)) => Some(max_len),
+                    _ => None,
+                };
+                Match::new(
+                    &input[start..span.end],
+                    start,
+                    name,
+                    expr.op,
+                    var.modifier == Some(Modifier::Explode),
+                    max_len,
+                )
+            }));
+        }
+        ms
+    }
+
+    /// Searches for a way to split `input[start..]` into the expressions from `expr_index`
+    /// in which repeated variables have consistent values.
+    fn split(
+        &self,
+        input: &str,
+        expr_index: usize,
+        start: usize,
+        spans: &mut Vec<Option<Range<usize>>>,
+        budget: &mut usize,
+    ) -> bool {
+        let regexes = self.split_regexes.get_or_init(|| SplitRegexes::new(self));
+        let start = if expr_index == 0 {
+            match regexes.head.find(input) {
+                Some(m) => m.end(),
+                None => return false,
+            }
+        } else {
+            start
+        };
+        let Some(expr) = self.exprs.get(expr_index) else {
+            return start == input.len();
+        };
+        let (part, _) = &regexes.exprs[expr_index];
+        let group_base = self.vars[expr.vars.start].groups[0] - 1;
+        let first_end = if expr_index + 1 == self.exprs.len() {
+            input.len()
+        } else {
+            start
+        };
+        for end in (first_end..=input.len()).rev() {
+            if !input.is_char_boundary(end) {
+                continue;
+            }
+            // `part` scans `input[start..end]` and `tail` scans `input[end..]`.
+            let Some(rest) = budget.checked_sub(input.len() - start) else {
+                return false;
+            };
+            *budget = rest;
+            if let Some((_, tail)) = regexes.exprs.get(expr_index + 1)
+                && !tail.is_match(&input[end..])
+            {
+                continue;
+            }
+            let Some(captures) = part.captures(&input[start..end]) else {
+                continue;
+            };
+            for var_index in expr.vars.clone() {
+                let var = &self.vars[var_index];
+                let m = var
+                    .groups
+                    .iter()
+                    .find_map(|&group| captures.get(group - group_base));
+                spans[var_index] = m.map(|m| start + m.start()..start + m.end());
+            }
+            let ms = self.to_matches(input, spans[..expr.vars.end].to_vec());
+            if is_consistent(&ms) && self.split(input, expr_index + 1, end, spans, budget) {
+                return true;
             }
         }
-        Some(Captures { template: self, ms })
+        for var_index in expr.vars.clone() {
+            spans[var_index] = None;
+        }
+        false
     }
     fn var_name(&self, index: usize) -> &str {
-        &self.source[self.exprs[index].var_name_range.clone()]
+        &self.source[self.vars[index].name_range.clone()]
     }
 
     pub fn var_names(&self) -> impl Iterator<Item = &str> {
-        (0..self.exprs.len()).map(|i| self.var_name(i))
+        (0..self.vars.len()).map(|i| self.var_name(i))
     }
     pub fn find_var_name(&self, name: &str) -> Option<usize> {
-        (0..self.exprs.len()).find(|&i| self.var_name(i) == name)
+        (0..self.vars.len()).find(|&i| self.var_name(i) == name)
+    }
+
+    /// Returns the specificity of the template used to rank templates that match the same URI.
+    pub fn specificity(&self) -> Specificity {
+        let mut literal_len = 0;
+        for segment in &self.segments {
+            match segment {
+                Segment::Literals { len } => literal_len += len,
+                Segment::LiteralsNeedEncode { len } => literal_len += len * 3,
+                Segment::Expr => {}
+            }
+        }
+        let wide_exprs = self
+            .exprs
+            .iter()
+            .filter(|expr| {
+                expr.allow_reserved()
+                    || self.vars[expr.vars.clone()]
+                        .iter()
+                        .any(|var| var.modifier == Some(Modifier::Explode))
+            })
+            .count();
+        Specificity {
+            literal_len,
+            wide_exprs: Reverse(wide_exprs),
+            exprs: self.exprs.len(),
+        }
+    }
+
+    /// Compares the specificity of two templates.
+    ///
+    /// `Ordering::Greater` means that `self` is more specific than `other`.
+    pub fn cmp_specificity(&self, other: &Self) -> Ordering {
+        self.specificity().cmp(&other.specificity())
+    }
+
+    /// Returns a URI that can be captured by both `self` and `other`, if any.
+    ///
+    /// `None` means that no URI matches both templates.
+    ///
+    /// If a template uses a variable more than once, only a limited number of URIs are examined,
+    /// so `None` may be returned even if some URI is captured by both templates.
+    pub fn overlaps(&self, other: &Self) -> Option<String> {
+        let a = automaton::Nfa::new(&self.regex);
+        let b = automaton::Nfa::new(&other.regex);
+        if !self.has_repeated_var() && !other.has_repeated_var() {
+            return automaton::intersection_witness(&a, &b);
+        }
+        automaton::intersection_witnesses(&a, &b, MAX_WITNESS_STATES)
+            .find(|uri| self.captures(uri).is_some() && other.captures(uri).is_some())
+    }
+
+    /// Returns `true` if every URI captured by `self` is also captured by `other`.
+    ///
+    /// `/v1/users/{id}` is a subset of `/v1/{+rest}`.
+    ///
+    /// If a template uses a variable more than once, the result is conservative:
+    /// `false` may be returned even if `self` is a subset of `other`.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        if other.has_repeated_var() {
+            return self.source == other.source;
+        }
+        automaton::difference_witness(
+            &automaton::Nfa::new(&self.regex),
+            &automaton::Nfa::new(&other.regex),
+        )
+        .is_none()
+    }
+    fn has_repeated_var(&self) -> bool {
+        let names: Vec<_> = self.var_names().collect();
+        (0..names.len()).any(|i| names[..i].contains(&names[i]))
+    }
+
+    /// Reports parts of the template that `captures` cannot reverse unambiguously.
+    pub fn lint(&self) -> Vec<Lint> {
+        lint::lint(self)
     }
 }
 
+/// Specificity of a template.
+///
+/// Templates are compared by the following criteria, in order:
+///
+/// 1. Templates with longer literals are more specific. (`/users/me` > `/users/{id}`)
+/// 2. Templates with fewer expressions that can match reserved characters
+///    (`{+var}`, `{#var}` and exploded variables) are more specific. (`/files/{name}` > `/files/{+path}`)
+/// 3. Templates with more expressions are more specific. (`/{id}{.format}` > `/{id}`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity {
+    literal_len: usize,
+    wide_exprs: Reverse<usize>,
+    exprs: usize,
+}
+
+/// `literals` excluding `pct-encoded`
+fn is_literal(c: char) -> bool {
+    is_unreserved(c) || is_reserved(c) || is_ucschar(c) || is_iprivate(c)
+}
+fn is_ucschar(c: char) -> bool {
+    matches!(c,
+        '\u{A0}'..='\u{D7FF}'
+            | '\u{F900}'..='\u{FDCF}'
+            | '\u{FDF0}'..='\u{FFEF}'
+            | '\u{10000}'..='\u{1FFFD}'
+            | '\u{20000}'..='\u{2FFFD}'
+            | '\u{30000}'..='\u{3FFFD}'
+            | '\u{40000}'..='\u{4FFFD}'
+            | '\u{50000}'..='\u{5FFFD}'
+            | '\u{60000}'..='\u{6FFFD}'
+            | '\u{70000}'..='\u{7FFFD}'
+            | '\u{80000}'..='\u{8FFFD}'
+            | '\u{90000}'..='\u{9FFFD}'
+            | '\u{A0000}'..='\u{AFFFD}'
+            | '\u{B0000}'..='\u{BFFFD}'
+            | '\u{C0000}'..='\u{CFFFD}'
+            | '\u{D0000}'..='\u{DFFFD}'
+            | '\u{E1000}'..='\u{EFFFD}'
+    )
+}
+fn is_iprivate(c: char) -> bool {
+    matches!(c, '\u{E000}'..='\u{F8FF}' | '\u{F0000}'..='\u{FFFFD}' | '\u{100000}'..='\u{10FFFD}')
+}
+
+/// `op-reserve`
+fn is_op_reserve(c: char) -> bool {
+    matches!(c, '=' | ',' | '!' | '@' | '|')
+}
+
 fn is_unreserved(c: char) -> bool {
     matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~')
 }
@@ -269,6 +1012,74 @@ fn is_reserved(c: char) -> bool {
 }
 const RE_RESERVED: &str = r":/?#\[\]@!$&'()*+,;=";
 
+/// Maximum number of states examined by [`UriTemplate::overlaps`] for templates with repeated variables.
+const MAX_WITNESS_STATES: usize = 10000;
+
+/// Maximum number of bytes scanned by the regexes of the splits tried by [`UriTemplate::captures`].
+///
+/// Each split scans the rest of the input, so the number of splits tried decreases as the input gets longer.
+const MAX_SPLIT_SCAN_LEN: usize = 1 << 18;
+
+/// Regexes used to split the input into expressions one by one.
+#[derive(Clone)]
+struct SplitRegexes {
+    /// Literals before the first expression.
+    head: Regex,
+    /// For each expression, the regex of the expression and the following literals,
+    /// and the regex of the rest of the template from the expression.
+    exprs: Vec<(Regex, Regex)>,
+}
+impl SplitRegexes {
+    fn new(template: &UriTemplate) -> Self {
+        let re = template.regex.as_str();
+        let end = re.len() - 1;
+        let ranges: Vec<_> = template
+            .exprs
+            .iter()
+            .map(|e| e.regex_range.clone())
+            .collect();
+        let head_end = ranges.first().map_or(end, |r| r.start);
+        let exprs = ranges
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let part_end = ranges.get(i + 1).map_or(end, |r| r.start);
+                let part = Regex::new(&format!("^(?:{})$", &re[r.start..part_end])).unwrap();
+                let tail = Regex::new(&format!("^{}", &re[r.start..])).unwrap();
+                (part, tail)
+            })
+            .collect();
+        Self {
+            head: Regex::new(&re[..head_end]).unwrap(),
+            exprs,
+        }
+    }
+}
+
+/// Returns `true` if the matches are within the length of their prefix modifiers
+/// and the occurrences of each variable have consistent values.
+fn is_consistent(ms: &[Option<Match>]) -> bool {
+    let ms: Vec<_> = ms.iter().flatten().collect();
+    if !ms.iter().all(|m| m.is_within_max_len()) {
+        return false;
+    }
+    for (i, m) in ms.iter().enumerate() {
+        for prev in &ms[..i] {
+            if prev.name == m.name && !prev.is_consistent_with(m) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn truncate_chars(s: &str, max_len: usize) -> &str {
+    match s.char_indices().nth(max_len) {
+        Some((index, _)) => &s[..index],
+        None => s,
+    }
+}
+
 fn encode_char(ch: char, out: &mut String) {
     for b in ch.encode_utf8(&mut [0; 4]).as_bytes() {
         write!(out, "%{b:02X}").unwrap();
@@ -331,9 +1142,10 @@ impl<'a> Decoder<'a> {
             self.source_index += valid.len() * 3;
             self.out.push_str(valid);
             if !check.invalid().is_empty() {
+                let len = check.invalid().len() * 3;
                 return Err(Error::new(
                     self.source,
-                    self.source_index,
+                    self.source_index..self.source_index + len,
                     ErrorKind::InvalidUtf8,
                 ));
             }
@@ -347,6 +1159,29 @@ impl<'a> Decoder<'a> {
     }
 }
 
+/// Decodes a percent-encoded value. Strings that cannot be decoded are kept as is.
+fn decode_value(value: Value) -> Value {
+    fn decode(s: Cow<str>) -> Cow<str> {
+        if s.contains('%')
+            && let Ok(decoded) = decode_str(&s, 0)
+        {
+            Cow::Owned(decoded)
+        } else {
+            s
+        }
+    }
+    match value {
+        Value::String(s) => Value::String(decode(s)),
+        Value::List(items) => Value::List(items.into_iter().map(decode).collect()),
+        Value::AssocArray(items) => Value::AssocArray(
+            items
+                .into_iter()
+                .map(|(k, v)| (decode(k), decode(v)))
+                .collect(),
+        ),
+    }
+}
+
 fn decode_str(s: &str, source_index: usize) -> Result<String> {
     let mut out = Decoder::new(s, source_index);
     for d in DecodedIter::new(s) {
@@ -452,13 +1287,39 @@ impl Captures<'_> {
         }
     }
 
+    /// Returns the match of the variable named `name`.
+    ///
+    /// If the variable appears more than once, an occurrence without a prefix modifier is preferred.
     pub fn name(&self, name: &str) -> Option<&Match<'_>> {
-        for (expr, m) in self.template.exprs.iter().zip(&self.ms) {
-            if &self.template.source[expr.var_name_range.clone()] == name {
-                return m.as_ref();
-            }
+        self.iter()
+            .filter(|&(n, _)| n == name)
+            .filter_map(|(_, m)| m)
+            .min_by_key(|m| Reverse(m.max_len.unwrap_or(usize::MAX)))
+    }
+
+    /// Returns the matches of all occurrences of the variable named `name`.
+    ///
+    /// All of them have the same decoded value,
+    /// except that occurrences with a prefix modifier have only the prefix of the value.
+    pub fn all<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s Match<'s>> {
+        self.iter()
+            .filter(move |&(n, _)| n == name)
+            .filter_map(|(_, m)| m)
+    }
+
+    /// Decodes the value of the variable named `name` and parses it with [`FromStr`](std::str::FromStr).
+    pub fn parse<T: std::str::FromStr>(
+        &self,
+        name: &str,
+    ) -> std::result::Result<T, ParseError<T::Err>> {
+        match self.name(name) {
+            Some(m) => m.parse(),
+            None => Err(ParseError {
+                name: name.to_string(),
+                span: None,
+                kind: ParseErrorKind::NotCaptured,
+            }),
         }
-        None
     }
     pub fn get(&self, i: usize) -> Option<&Match<'_>> {
         self.ms.get(i)?.as_ref()
@@ -476,57 +1337,261 @@ impl Captures<'_> {
 
 #[derive(Debug)]
 pub struct Match<'a> {
-    m: regex::Match<'a>,
+    source: &'a str,
+    start: usize,
     name: &'a str,
     op: Option<Operator>,
+    explode: bool,
+    max_len: Option<usize>,
 }
 impl<'a> Match<'a> {
-    fn new(m: regex::Match<'a>, name: &'a str, op: Option<Operator>) -> Self {
-        Self { m, name, op }
+    fn new(
+        source: &'a str,
+        start: usize,
+        name: &'a str,
+        op: Option<Operator>,
+        explode: bool,
+        max_len: Option<usize>,
+    ) -> Self {
+        Self {
+            source,
+            start,
+            name,
+            op,
+            explode,
+            max_len,
+        }
     }
     pub fn name(&self) -> &str {
         self.name
     }
     pub fn value(&self) -> Result<Cow<'_, str>> {
-        match self.op {
-            None => Ok(Cow::Owned(decode_str(self.m.as_str(), 0)?)),
-            Some(Operator::Reserved | Operator::Fragment) => Ok(Cow::Borrowed(self.source())),
+        let source = if self.explode {
+            let mut s = String::new();
+            for (i, (key, value)) in self.exploded_items().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                if let Some(key) = key {
+                    s.push_str(key);
+                    s.push(',');
+                }
+                s.push_str(value);
+            }
+            Cow::Owned(s)
+        } else {
+            Cow::Borrowed(self.source)
+        };
+        self.decode(source)
+    }
+
+    /// Returns the items of a list value.
+    ///
+    /// For `/files{/path*}` and `/files/a/b`, this returns `["a", "b"]`.
+    pub fn values(&self) -> Result<Vec<Cow<'_, str>>> {
+        let items: Vec<&str> = if self.explode {
+            let named = self.op.is_some_and(|op| op.named());
+            self.source
+                .split(self.sep())
+                .map(|item| if named { self.strip_name(item) } else { item })
+                .collect()
+        } else {
+            self.source.split(',').collect()
+        };
+        items.into_iter().map(|s| self.decode(s.into())).collect()
+    }
+
+    /// Returns the entries of an associative array value.
+    ///
+    /// For `{?filters*}` and `?a=1&b=2`, this returns `[("a", "1"), ("b", "2")]`.
+    /// If the number of items in an unexploded value is odd, the value of the last key is empty.
+    pub fn pairs(&self) -> Result<Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+        let mut pairs = Vec::new();
+        if self.explode {
+            for item in self.source.split(self.sep()) {
+                pairs.push(item.split_once('=').unwrap_or((item, "")));
+            }
+        } else {
+            let mut items = self.source.split(',');
+            while let Some(key) = items.next() {
+                pairs.push((key, items.next().unwrap_or("")));
+            }
+        }
+        pairs
+            .into_iter()
+            .map(|(key, value)| Ok((self.decode(key.into())?, self.decode(value.into())?)))
+            .collect()
+    }
+    fn sep(&self) -> &'static str {
+        self.op.map_or(",", |op| op.sep())
+    }
+    fn strip_name<'b>(&self, item: &'b str) -> &'b str {
+        match item.strip_prefix(self.name) {
+            Some("") => "",
+            Some(value) => value.strip_prefix('=').unwrap_or(item),
+            None => item,
+        }
+    }
+    fn decode<'b>(&self, s: Cow<'b, str>) -> Result<Cow<'b, str>> {
+        if self.op.is_some_and(|op| op.allow_reserved()) || !s.contains('%') {
+            Ok(s)
+        } else {
+            Ok(Cow::Owned(decode_str(&s, 0)?))
+        }
+    }
+    fn exploded_items(&self) -> impl Iterator<Item = (Option<&'a str>, &'a str)> {
+        let name = self.name;
+        let named = self.op.is_some_and(|op| op.named());
+        self.source
+            .split(self.sep())
+            .map(move |item| match item.split_once('=') {
+                Some((key, value)) if !named || key != name => (Some(key), value),
+                Some((_, value)) => (None, value),
+                None if named && item != name => (Some(item), ""),
+                None if named => (None, ""),
+                None => (None, item),
+            })
+    }
+    /// Decodes the value and parses it with [`FromStr`](std::str::FromStr).
+    pub fn parse<T: std::str::FromStr>(&self) -> std::result::Result<T, ParseError<T::Err>> {
+        let error = |kind| ParseError {
+            name: self.name.to_string(),
+            span: Some(self.start()..self.end()),
+            kind,
+        };
+        let value = self.value().map_err(|e| error(ParseErrorKind::Decode(e)))?;
+        value.parse().map_err(|e| error(ParseErrorKind::Parse(e)))
+    }
+    /// Returns `true` if [`Match::to_value`] keeps the percent-encoding.
+    fn is_encoded(&self) -> bool {
+        self.op.is_some_and(|op| op.allow_reserved())
+    }
+    /// Returns the value used when the captures are used as [`Vars`].
+    fn to_value(&self) -> Option<Value<'a>> {
+        if !self.explode {
+            return self
+                .decode(Cow::Borrowed(self.source))
+                .ok()
+                .map(Value::String);
+        }
+        let items: Vec<_> = self.exploded_items().collect();
+        if items.iter().any(|(key, _)| key.is_some()) {
+            let mut pairs = Vec::new();
+            for (key, value) in items {
+                let key = self.decode(Cow::Borrowed(key.unwrap_or(self.name))).ok()?;
+                pairs.push((key, self.decode(Cow::Borrowed(value)).ok()?));
+            }
+            Some(Value::AssocArray(pairs))
+        } else {
+            let values = items
+                .into_iter()
+                .map(|(_, value)| self.decode(Cow::Borrowed(value)));
+            values.collect::<Result<_>>().ok().map(Value::List)
+        }
+    }
+    /// Returns `true` if the number of characters does not exceed the prefix modifier.
+    ///
+    /// Percent-encoded characters are counted as one character.
+    fn is_within_max_len(&self) -> bool {
+        let Some(max_len) = self.max_len else {
+            return true;
+        };
+        let len = match decode_str(self.source, self.start) {
+            Ok(s) => s.chars().count(),
+            Err(_) => self.source.chars().count(),
+        };
+        len <= max_len
+    }
+    /// Returns `true` if both matches can be occurrences of the same value.
+    ///
+    /// A match captured with a prefix modifier only has the prefix of the value.
+    fn is_consistent_with(&self, other: &Match) -> bool {
+        let max_len = match (self.max_len, other.max_len) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b).unwrap_or(usize::MAX),
+        };
+        match (self.value(), other.value()) {
+            (Ok(a), Ok(b)) => truncate_chars(&a, max_len) == truncate_chars(&b, max_len),
+            _ => self.source == other.source,
         }
     }
     pub fn source(&self) -> &str {
-        self.m.as_str()
+        self.source
     }
     pub fn start(&self) -> usize {
-        self.m.start()
+        self.start
     }
     pub fn end(&self) -> usize {
-        self.m.end()
+        self.start + self.source.len()
     }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Clone, Copy, Debug, Display)]
-enum ErrorKind {
-    InvalidExpression,
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// `{` without a matching `}`
+    UnclosedExpression,
+    /// `{}`
+    EmptyExpression,
+    /// `}` outside of an expression
+    UnexpectedCloseBrace,
+    /// `{` inside of an expression
+    NestedBrace,
+    /// Operator reserved for future extensions (`=`, `,`, `!`, `@`, `|`)
+    UnknownOperator,
+    /// Variable name that does not match `varname`
+    InvalidVarName,
+    /// Malformed prefix or explode modifier
+    InvalidModifier,
+    /// Character not allowed in literals
+    InvalidLiteral,
+    /// `%` not followed by two hexadecimal digits
+    InvalidPercentEncoding,
+    /// Percent-encoded bytes that are not valid UTF-8
     InvalidUtf8,
+    /// Prefix modifier applied to a list or an associative array
+    PrefixOnCompositeValue,
+    /// Template too large to build a regex that matches it
+    TooComplex,
 }
 
 #[derive(Clone, Debug)]
 pub struct Error {
     source: String,
-    source_index: usize,
+    span: Range<usize>,
     kind: ErrorKind,
 }
 
 impl Error {
-    fn new(source: &str, source_index: usize, kind: ErrorKind) -> Self {
+    fn new(source: &str, span: Range<usize>, kind: ErrorKind) -> Self {
         Self {
             source: source.to_string(),
-            source_index,
+            span,
             kind,
         }
     }
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the text in which the error was found.
+    ///
+    /// This is the template for errors reported by [`UriTemplate::new`] and [`UriTemplate::try_expand`],
+    /// and the captured text for errors reported by [`Match::value`].
+    /// This is empty for errors reported by [`Router::new`].
+    pub fn text(&self) -> &str {
+        &self.source
+    }
+    pub fn source_index(&self) -> usize {
+        self.span.start
+    }
+
+    /// Returns the byte range of [`Error::text`] that caused the error.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
 }
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -534,10 +1599,71 @@ impl fmt::Display for Error {
             f,
             "{} (\"{} >>>> {}\")",
             self.kind,
-            &self.source[..self.source_index],
-            &self.source[self.source_index..],
+            &self.source[..self.span.start],
+            &self.source[self.span.start..],
         )
     }
 }
 
 impl std::error::Error for Error {}
+
+/// Error returned by [`Match::parse`] and [`Captures::parse`].
+#[derive(Clone, Debug)]
+pub struct ParseError<E> {
+    name: String,
+    span: Option<Range<usize>>,
+    kind: ParseErrorKind<E>,
+}
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ParseErrorKind<E> {
+    /// The variable was not captured.
+    NotCaptured,
+    /// The value could not be decoded.
+    Decode(Error),
+    /// The decoded value could not be parsed.
+    Parse(E),
+}
+
+impl<E> ParseError<E> {
+    /// Returns the name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the byte range of the captured text in the input.
+    ///
+    /// This is `None` if the variable was not captured.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+    pub fn kind(&self) -> &ParseErrorKind<E> {
+        &self.kind
+    }
+    pub fn into_kind(self) -> ParseErrorKind<E> {
+        self.kind
+    }
+}
+impl<E: fmt::Display> fmt::Display for ParseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "variable `{}`", self.name)?;
+        if let Some(span) = &self.span {
+            write!(f, " at {}..{}", span.start, span.end)?;
+        }
+        match &self.kind {
+            ParseErrorKind::NotCaptured => write!(f, " is not captured"),
+            ParseErrorKind::Decode(e) => write!(f, ": {}", e.kind()),
+            ParseErrorKind::Parse(e) => write!(f, ": {e}"),
+        }
+    }
+}
+impl<E: std::error::Error + 'static> std::error::Error for ParseError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::NotCaptured => None,
+            ParseErrorKind::Decode(e) => Some(e),
+            ParseErrorKind::Parse(e) => Some(e),
+        }
+    }
+}
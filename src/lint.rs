@@ -0,0 +1,193 @@
+use parse_display::Display;
+use std::fmt;
+use std::ops::Range;
+
+use crate::{Expr, Modifier, Segment, UriTemplate, VarSpec, is_reserved, is_unreserved};
+
+/// Problem found by [`UriTemplate::lint`].
+#[derive(Clone, Debug)]
+pub struct Lint {
+    kind: LintKind,
+    span: Range<usize>,
+    message: String,
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LintKind {
+    /// Captured text can be split between variables in more than one way. (`{a}{b}`)
+    AmbiguousCapture,
+    /// Variable used with different operators or modifiers. (`{a}{?a}`)
+    InconsistentOperator,
+    /// Reserved expansion that can contain the following literal. (`/{+x}/{+y}`)
+    ReservedSwallowsLiteral,
+}
+
+impl Lint {
+    fn new(kind: LintKind, span: Range<usize>, message: String) -> Self {
+        Self {
+            kind,
+            span,
+            message,
+        }
+    }
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+enum Part {
+    /// First character of the literals as it appears in the expanded URI.
+    Literal(char),
+    Expr(usize),
+}
+
+pub(crate) fn lint(t: &UriTemplate) -> Vec<Lint> {
+    let mut parts = Vec::new();
+    let mut index = 0;
+    let mut expr_index = 0;
+    for segment in &t.segments {
+        match segment {
+            Segment::Literals { len } => {
+                parts.push((
+                    Part::Literal(t.source[index..].chars().next().unwrap()),
+                    index,
+                ));
+                index += len;
+            }
+            Segment::LiteralsNeedEncode { len } => {
+                parts.push((Part::Literal('%'), index));
+                index += len;
+            }
+            Segment::Expr => {
+                parts.push((Part::Expr(expr_index), index));
+                index += t.exprs[expr_index].len;
+                expr_index += 1;
+            }
+        }
+    }
+
+    let mut lints = Vec::new();
+    for (i, (part, start)) in parts.iter().enumerate() {
+        let Part::Expr(expr_index) = *part else {
+            continue;
+        };
+        let expr = &t.exprs[expr_index];
+        let var_specs = &t.vars[expr.vars.clone()];
+        let span = *start..start + expr.len;
+        let text = &t.source[span.clone()];
+        if let Some(var) = var_specs[..var_specs.len() - 1]
+            .iter()
+            .find(|var| var.modifier == Some(Modifier::Explode))
+        {
+            lints.push(Lint::new(
+                LintKind::AmbiguousCapture,
+                var.range.clone(),
+                format!(
+                    "exploded variable `{}` in `{text}` can capture the values of the following variables",
+                    &t.source[var.name_range.clone()]
+                ),
+            ));
+        }
+        match parts.get(i + 1) {
+            Some(&(Part::Expr(next_index), next_start)) => {
+                let next = &t.exprs[next_index];
+                let can_contain_next = match next.first().chars().next() {
+                    Some(c) => can_contain(expr, var_specs, c),
+                    None => true,
+                };
+                if can_contain_next {
+                    let next_span = next_start..next_start + next.len;
+                    lints.push(Lint::new(
+                        LintKind::AmbiguousCapture,
+                        span.start..next_span.end,
+                        format!(
+                            "`{text}` and `{}` can be split in more than one way",
+                            &t.source[next_span]
+                        ),
+                    ));
+                }
+            }
+            Some(&(Part::Literal(c), literal_start)) if expr.allow_reserved() => {
+                let has_expr_after = parts[i + 1..]
+                    .iter()
+                    .any(|(part, _)| matches!(part, Part::Expr(_)));
+                if has_expr_after && can_contain(expr, var_specs, c) {
+                    let literal_end = parts[i + 1..]
+                        .iter()
+                        .find(|(part, _)| matches!(part, Part::Expr(_)))
+                        .map_or(t.source.len(), |&(_, start)| start);
+                    lints.push(Lint::new(
+                        LintKind::ReservedSwallowsLiteral,
+                        span.start..literal_end,
+                        format!(
+                            "`{text}` can contain `{}`, so the following captures are ambiguous",
+                            &t.source[literal_start..literal_end]
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (i, var) in t.vars.iter().enumerate() {
+        let name = &t.source[var.name_range.clone()];
+        let Some(first) = t.vars[..i]
+            .iter()
+            .find(|v| &t.source[v.name_range.clone()] == name)
+        else {
+            continue;
+        };
+        let op = t.exprs[var.expr_index].op;
+        let first_op = t.exprs[first.expr_index].op;
+        if op != first_op || var.modifier != first.modifier {
+            lints.push(Lint::new(
+                LintKind::InconsistentOperator,
+                var.range.clone(),
+                format!(
+                    "variable `{name}` is used as `{}` and `{}`",
+                    var_text(t, first),
+                    var_text(t, var)
+                ),
+            ));
+        }
+    }
+    lints.sort_by_key(|lint| (lint.span.start, lint.span.end));
+    lints
+}
+
+/// Returns the variable with its operator, such as `{?a}`.
+fn var_text(t: &UriTemplate, var: &VarSpec) -> String {
+    let op = t.exprs[var.expr_index].op.map(|op| op.to_char());
+    format!(
+        "{{{}{}}}",
+        op.map_or(String::new(), String::from),
+        &t.source[var.range.clone()]
+    )
+}
+
+/// Returns `true` if the text captured by `expr` can contain `c`.
+fn can_contain(expr: &Expr, var_specs: &[VarSpec], c: char) -> bool {
+    let value = is_unreserved(c) || c == '%' || (expr.allow_reserved() && is_reserved(c));
+    let sep = expr.sep().starts_with(c);
+    var_specs.iter().any(|var| {
+        if var.modifier == Some(Modifier::Explode) {
+            value || sep || (expr.named() && c == '=')
+        } else {
+            let value = value || (!expr.allow_reserved() && c == ',') || (expr.named() && c == '=');
+            value && !(var_specs.len() > 1 && sep)
+        }
+    })
+}
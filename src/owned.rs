@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::{Captures, Match, Operator, ParseError, ParseErrorKind, Result};
+
+/// Captures that own the captured text.
+///
+/// Created by [`Captures::into_owned`].
+#[derive(Clone, Debug)]
+pub struct OwnedCaptures {
+    ms: Vec<(String, Option<OwnedMatch>)>,
+}
+
+impl Captures<'_> {
+    /// Converts the captures to captures that do not borrow the template or the input.
+    pub fn into_owned(self) -> OwnedCaptures {
+        OwnedCaptures {
+            ms: self
+                .iter()
+                .map(|(name, m)| (name.to_string(), m.map(OwnedMatch::new)))
+                .collect(),
+        }
+    }
+}
+
+impl OwnedCaptures {
+    /// Returns the match of the variable named `name`. See [`Captures::name`].
+    pub fn name(&self, name: &str) -> Option<&OwnedMatch> {
+        self.iter()
+            .filter(|&(n, _)| n == name)
+            .filter_map(|(_, m)| m)
+            .min_by_key(|m| Reverse(m.max_len.unwrap_or(usize::MAX)))
+    }
+
+    /// Returns the matches of all occurrences of the variable named `name`.
+    pub fn all<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s OwnedMatch> {
+        self.iter()
+            .filter(move |&(n, _)| n == name)
+            .filter_map(|(_, m)| m)
+    }
+
+    /// Parses the value of the variable named `name` with [`FromStr`].
+    pub fn parse<T: FromStr>(&self, name: &str) -> std::result::Result<T, ParseError<T::Err>> {
+        match self.name(name) {
+            Some(m) => m.parse(),
+            None => Err(ParseError {
+                name: name.to_string(),
+                span: None,
+                kind: ParseErrorKind::NotCaptured,
+            }),
+        }
+    }
+    pub fn get(&self, i: usize) -> Option<&OwnedMatch> {
+        self.ms.get(i)?.1.as_ref()
+    }
+    pub fn len(&self) -> usize {
+        self.ms.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ms.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&OwnedMatch>)> {
+        self.ms.iter().map(|(name, m)| (name.as_str(), m.as_ref()))
+    }
+}
+
+/// Collects the decoded values of the captured variables.
+///
+/// Variables that were not captured or could not be decoded are omitted.
+impl From<OwnedCaptures> for BTreeMap<String, String> {
+    fn from(captures: OwnedCaptures) -> Self {
+        let mut map = BTreeMap::new();
+        for (name, _) in captures.iter() {
+            if !map.contains_key(name)
+                && let Some(OwnedMatch {
+                    value: Ok(value), ..
+                }) = captures.name(name)
+            {
+                map.insert(name.to_string(), value.clone());
+            }
+        }
+        map
+    }
+}
+
+/// Match that owns the captured text.
+#[derive(Clone, Debug)]
+pub struct OwnedMatch {
+    source: String,
+    start: usize,
+    name: String,
+    op: Option<Operator>,
+    explode: bool,
+    max_len: Option<usize>,
+    value: Result<String>,
+}
+
+impl OwnedMatch {
+    fn new(m: &Match) -> Self {
+        Self {
+            source: m.source.to_string(),
+            start: m.start,
+            name: m.name.to_string(),
+            op: m.op,
+            explode: m.explode,
+            max_len: m.max_len,
+            value: m.value().map(|value| value.into_owned()),
+        }
+    }
+    pub(crate) fn as_match(&self) -> Match<'_> {
+        Match::new(
+            &self.source,
+            self.start,
+            &self.name,
+            self.op,
+            self.explode,
+            self.max_len,
+        )
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the decoded value. See [`Match::value`].
+    pub fn value(&self) -> Result<&str> {
+        self.value.as_deref().map_err(Clone::clone)
+    }
+
+    /// Returns the items of a list value. See [`Match::values`].
+    pub fn values(&self) -> Result<Vec<String>> {
+        let m = self.as_match();
+        Ok(m.values()?.into_iter().map(|s| s.into_owned()).collect())
+    }
+
+    /// Returns the entries of an associative array value. See [`Match::pairs`].
+    pub fn pairs(&self) -> Result<Vec<(String, String)>> {
+        let m = self.as_match();
+        Ok(m.pairs()?
+            .into_iter()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect())
+    }
+
+    /// Parses the decoded value with [`FromStr`].
+    pub fn parse<T: FromStr>(&self) -> std::result::Result<T, ParseError<T::Err>> {
+        self.as_match().parse()
+    }
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    pub fn start(&self) -> usize {
+        self.start
+    }
+    pub fn end(&self) -> usize {
+        self.start + self.source.len()
+    }
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end()
+    }
+}
@@ -0,0 +1,234 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::vars::into_string;
+use crate::{Captures, Error, Match, UriTemplate, Value, Vars};
+
+/// Rule that rewrites URIs matching one template into another template.
+///
+/// Variables of `to` take the values captured by `from` with the same name.
+/// Use [`RewriteRule::rename`] to take them from a variable with another name,
+/// and [`RewriteRule::default`] to give them a value when nothing is captured.
+/// Variables without a value are undefined and omitted from the result, as in [`UriTemplate::expand`].
+#[derive(Clone, Debug)]
+pub struct RewriteRule {
+    from: UriTemplate,
+    to: UriTemplate,
+    renames: Vec<(String, String)>,
+    defaults: Vec<(String, String)>,
+    required: Vec<String>,
+}
+
+impl RewriteRule {
+    pub fn new(from: UriTemplate, to: UriTemplate) -> Self {
+        Self {
+            from,
+            to,
+            renames: Vec::new(),
+            defaults: Vec::new(),
+            required: Vec::new(),
+        }
+    }
+
+    /// Uses the value captured as `from` for the variable `to` of the target template.
+    pub fn rename(mut self, from: &str, to: &str) -> Self {
+        self.renames.push((to.to_string(), from.to_string()));
+        self
+    }
+
+    /// Uses `value` for the variable `name` of the target template when it has no captured value.
+    pub fn default(mut self, name: &str, value: &str) -> Self {
+        self.defaults.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Skips this rule when the variable `name` of the target template has no value.
+    pub fn require(mut self, name: &str) -> Self {
+        self.required.push(name.to_string());
+        self
+    }
+    pub fn from(&self) -> &UriTemplate {
+        &self.from
+    }
+    pub fn to(&self) -> &UriTemplate {
+        &self.to
+    }
+
+    /// Rewrites `input` with this rule.
+    ///
+    /// The rule is skipped with [`SkipReason::MissingVar`] when a variable marked by [`RewriteRule::require`] has no value,
+    /// or when no variable of an expression without an operator or with `+` has a value,
+    /// since the expression would then drop a part of the path such as the segment of `/users/{id}`.
+    pub fn apply(&self, input: &str) -> Result<String, SkipReason> {
+        let captures = self.from.captures(input).ok_or(SkipReason::NotMatched)?;
+        let vars = RuleVars {
+            rule: self,
+            captures: &captures,
+        };
+        if let Some(name) = self.missing_var(&vars) {
+            return Err(SkipReason::MissingVar(name.to_string()));
+        }
+        self.to.try_expand(vars).map_err(SkipReason::Expand)
+    }
+    fn missing_var(&self, vars: &RuleVars) -> Option<&str> {
+        if let Some(name) = self.required.iter().find(|name| !vars.has_value(name)) {
+            return Some(name);
+        }
+        let to = &self.to;
+        to.exprs
+            .iter()
+            .filter(|expr| expr.first().is_empty())
+            .find(|expr| {
+                expr.vars
+                    .clone()
+                    .all(|index| !vars.has_value(to.var_name(index)))
+            })
+            .map(|expr| to.var_name(expr.vars.start))
+    }
+}
+
+struct RuleVars<'a> {
+    rule: &'a RewriteRule,
+    captures: &'a Captures<'a>,
+}
+
+impl Vars for RuleVars<'_> {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        if let Some(m) = self.captured(name) {
+            return m.to_value();
+        }
+        let (_, value) = self.rule.defaults.iter().rev().find(|(n, _)| n == name)?;
+        Some(Value::String(Cow::Borrowed(value)))
+    }
+    fn is_encoded(&mut self, _index: usize, name: &str) -> bool {
+        self.captured(name).is_some_and(|m| m.is_encoded())
+    }
+}
+impl<'a> RuleVars<'a> {
+    /// Returns the match that gives the value of the variable `name` of the target template.
+    fn captured(&self, name: &str) -> Option<&'a Match<'a>> {
+        let source = match self.rule.renames.iter().rev().find(|(to, _)| to == name) {
+            Some((_, from)) => from,
+            None => name,
+        };
+        let m = self.captures.name(source)?;
+        m.to_value().is_some().then_some(m)
+    }
+    fn has_value(&self, name: &str) -> bool {
+        self.captured(name).is_some() || self.rule.defaults.iter().any(|(n, _)| n == name)
+    }
+}
+
+/// Reason why a rule was not applied.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The input did not match the source template.
+    NotMatched,
+    /// The required variable of the target template has neither a captured value nor a default value.
+    MissingVar(String),
+    /// The target template could not be expanded with the captured values.
+    Expand(Error),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::NotMatched => write!(f, "not matched"),
+            SkipReason::MissingVar(name) => write!(f, "variable `{name}` has no value"),
+            SkipReason::Expand(e) => write!(f, "cannot expand: {}", e.kind()),
+        }
+    }
+}
+
+/// Ordered list of [`RewriteRule`]s.
+///
+/// Rules are tried in the order they were registered, and the first rule that applies is used.
+#[derive(Clone, Debug, Default)]
+pub struct RewriteRules {
+    rules: Vec<RewriteRule>,
+}
+
+impl RewriteRules {
+    pub fn new(rules: impl IntoIterator<Item = RewriteRule>) -> Self {
+        Self {
+            rules: rules.into_iter().collect(),
+        }
+    }
+    pub fn push(&mut self, rule: RewriteRule) {
+        self.rules.push(rule);
+    }
+
+    /// Rewrites `input` with the first rule that applies.
+    pub fn apply(&self, input: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| rule.apply(input).ok())
+    }
+
+    /// Rewrites `input` with the first rule that applies and reports the rules skipped before it.
+    pub fn rewrite(&self, input: &str) -> Rewrite {
+        let mut skipped = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            match rule.apply(input) {
+                Ok(uri) => {
+                    return Rewrite {
+                        fired: Some((index, uri)),
+                        skipped,
+                    };
+                }
+                Err(reason) => skipped.push((index, reason)),
+            }
+        }
+        Rewrite {
+            fired: None,
+            skipped,
+        }
+    }
+    pub fn get(&self, index: usize) -> Option<&RewriteRule> {
+        self.rules.get(index)
+    }
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &RewriteRule> {
+        self.rules.iter()
+    }
+}
+
+impl FromIterator<RewriteRule> for RewriteRules {
+    fn from_iter<I: IntoIterator<Item = RewriteRule>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Result of [`RewriteRules::rewrite`].
+#[derive(Clone, Debug)]
+pub struct Rewrite {
+    fired: Option<(usize, String)>,
+    skipped: Vec<(usize, SkipReason)>,
+}
+
+impl Rewrite {
+    /// Returns the rewritten URI, or `None` if no rule applied.
+    pub fn uri(&self) -> Option<&str> {
+        Some(&self.fired.as_ref()?.1)
+    }
+
+    /// Returns the index of the rule that applied.
+    pub fn rule(&self) -> Option<usize> {
+        Some(self.fired.as_ref()?.0)
+    }
+
+    /// Returns the indexes of the rules that were tried and not applied, with the reasons.
+    pub fn skipped(&self) -> &[(usize, SkipReason)] {
+        &self.skipped
+    }
+    pub fn into_uri(self) -> Option<String> {
+        Some(self.fired?.1)
+    }
+}
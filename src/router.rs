@@ -0,0 +1,58 @@
+use regex::RegexSet;
+
+use crate::{Captures, Error, ErrorKind, Result, UriTemplate};
+
+/// Set of templates matched against a URI in a single scan.
+///
+/// When several templates match, the most specific one takes priority
+/// (see [`UriTemplate::specificity`]).
+/// Templates with the same specificity are prioritized in the order they were registered.
+#[derive(Clone, Debug)]
+pub struct Router<T> {
+    routes: Vec<(UriTemplate, T)>,
+    set: RegexSet,
+}
+
+impl<T> Router<T> {
+    /// Creates a router from templates and their values.
+    ///
+    /// Returns an error of [`ErrorKind::TooComplex`] if the templates are too large to be matched in a single scan.
+    pub fn new(routes: impl IntoIterator<Item = (UriTemplate, T)>) -> Result<Self> {
+        let mut routes: Vec<_> = routes.into_iter().collect();
+        routes.sort_by(|(a, _), (b, _)| b.cmp_specificity(a));
+        let Ok(set) = RegexSet::new(routes.iter().map(|(t, _)| t.regex.as_str())) else {
+            return Err(Error::new("", 0..0, ErrorKind::TooComplex));
+        };
+        Ok(Self { routes, set })
+    }
+
+    /// Returns the value and the captures of the matching template with the highest priority.
+    pub fn find<'a>(&'a self, input: &'a str) -> Option<(&'a T, Captures<'a>)> {
+        self.find_all(input).next()
+    }
+
+    /// Returns the values and the captures of all matching templates in priority order.
+    pub fn find_all<'a>(
+        &'a self,
+        input: &'a str,
+    ) -> impl Iterator<Item = (&'a T, Captures<'a>)> + 'a {
+        self.set
+            .matches(input)
+            .into_iter()
+            .filter_map(move |index| {
+                let (template, value) = &self.routes[index];
+                Some((value, template.captures(input)?))
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+    /// Returns the templates and their values in priority order.
+    pub fn iter(&self) -> impl Iterator<Item = (&UriTemplate, &T)> {
+        self.routes.iter().map(|(t, v)| (t, v))
+    }
+}
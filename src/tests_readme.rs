@@ -5,24 +5,29 @@
 //! [![Docs.rs](https://docs.rs/uri-template-ex/badge.svg)](https://docs.rs/uri-template-ex/)
 //! [![Actions Status](https://github.com/frozenlib/uri-template-ex/workflows/CI/badge.svg)](https://github.com/frozenlib/uri-template-ex/actions)
 //!
-//! RFC6570 URI Template Level 2 の実装
+//! RFC6570 URI Template Level 4 の実装
 //!
 //! ## 概要
 //!
-//! `uri-template-ex`は、[RFC6570] で定義されている URI Template Level 2 による URL の展開と変数の抽出（キャプチャ）を実装した crate です。
+//! `uri-template-ex`は、[RFC6570] で定義されている URI Template Level 4 による URL の展開と変数の抽出（キャプチャ）を実装した crate です。
 //!
 //! RFC6570 は変数の展開のみを定義しており抽出は定義していないこともあり、既存の URI Template の実装は変数の展開のみをサポートし、抽出はサポートしないものがほとんどです。この crate では URI Tempalte を同じ構文による変数の抽出もサポートします。
 //!
 //! ## 特徴
 //!
-//! - RFC6570 URI Template Level 2 による変数の展開
+//! - RFC6570 URI Template Level 4 による変数の展開
 //! - URI テンプレートからの変数値の抽出
+//! - 構造体に対する `#[derive(Vars)]` と `#[derive(FromCaptures)]` (`derive` feature)
+//! - コンパイル時に検証される `uri_template!` マクロ (`macros` feature)
 //!
-//! URI Template Level 2 では下記の 3 種類の変数を使用できます。
+//! URI Template Level 4 のすべての演算子と修飾子をサポートしています。
 //!
-//! - `{var}`
-//! - `{+var}`
-//! - `{#var}`
+//! - `{var}`, `{+var}`, `{#var}`, `{.var}`, `{/var}`, `{;var}`, `{?var}`, `{&var}`
+//! - 複数の変数: `{x,y}`
+//! - プレフィックス修飾子: `{var:3}`
+//! - 展開修飾子: `{list*}`
+//!
+//! 変数の値にはリストと連想配列も使用できます。
 //!
 //! ## インストール
 //!
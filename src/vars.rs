@@ -1,63 +1,149 @@
+use serde::Serialize;
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 use std::str;
 use std::{borrow::Cow, fmt};
 
-pub trait Vars {
-    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>>;
+use crate::{Captures, OwnedCaptures};
+
+/// Value of a variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value<'a> {
+    String(Cow<'a, str>),
+    List(Vec<Cow<'a, str>>),
+    AssocArray(Vec<(Cow<'a, str>, Cow<'a, str>)>),
 }
-impl Vars for () {
-    fn var(&mut self, _index: usize, _name: &str) -> Option<Cow<'_, str>> {
-        None
+impl Value<'_> {
+    /// Returns `true` if the value is an empty list or an empty associative array.
+    ///
+    /// Such values are treated as undefined in expansion.
+    pub fn is_undefined(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::List(items) => items.is_empty(),
+            Value::AssocArray(items) => items.is_empty(),
+        }
     }
 }
-impl<K> Vars for &HashMap<K, &str>
-where
-    K: std::borrow::Borrow<str> + Hash + Eq,
-{
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(Cow::Borrowed(self.get(name)?))
+
+pub trait ToValue {
+    fn to_value(&self) -> Value<'_>;
+}
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value<'_> {
+        (**self).to_value()
     }
 }
-impl<K> Vars for &HashMap<K, String>
-where
-    K: std::borrow::Borrow<str> + Hash + Eq,
-{
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(Cow::Borrowed(self.get(name)?))
+impl ToValue for str {
+    fn to_value(&self) -> Value<'_> {
+        Value::String(Cow::Borrowed(self))
     }
 }
-impl<K> Vars for &HashMap<K, &dyn fmt::Display>
-where
-    K: std::borrow::Borrow<str> + Hash + Eq,
-{
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(self.get(name)?.to_string().into())
+impl ToValue for String {
+    fn to_value(&self) -> Value<'_> {
+        Value::String(Cow::Borrowed(self))
     }
 }
-impl<K> Vars for &BTreeMap<K, &str>
-where
-    K: std::borrow::Borrow<str> + Ord,
-{
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(Cow::Borrowed(self.get(name)?))
+macro_rules! impl_to_value_by_display {
+    ($($t:ty),*) => {
+        $(
+            impl ToValue for $t {
+                fn to_value(&self) -> Value<'_> {
+                    Value::String(Cow::Owned(self.to_string()))
+                }
+            }
+        )*
+    };
+}
+impl_to_value_by_display!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+impl ToValue for dyn fmt::Display + '_ {
+    fn to_value(&self) -> Value<'_> {
+        Value::String(Cow::Owned(self.to_string()))
+    }
+}
+impl<T: AsRef<str>> ToValue for [T] {
+    fn to_value(&self) -> Value<'_> {
+        Value::List(self.iter().map(|s| Cow::Borrowed(s.as_ref())).collect())
+    }
+}
+impl<T: AsRef<str>> ToValue for Vec<T> {
+    fn to_value(&self) -> Value<'_> {
+        self.as_slice().to_value()
+    }
+}
+impl<K: AsRef<str>, V: AsRef<str>> ToValue for BTreeMap<K, V> {
+    fn to_value(&self) -> Value<'_> {
+        Value::AssocArray(
+            self.iter()
+                .map(|(k, v)| (Cow::Borrowed(k.as_ref()), Cow::Borrowed(v.as_ref())))
+                .collect(),
+        )
+    }
+}
+impl ToValue for Value<'_> {
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            Value::String(s) => Value::String(Cow::Borrowed(s)),
+            Value::List(items) => Value::List(items.iter().map(|s| Cow::Borrowed(&**s)).collect()),
+            Value::AssocArray(items) => Value::AssocArray(
+                items
+                    .iter()
+                    .map(|(k, v)| (Cow::Borrowed(&**k), Cow::Borrowed(&**v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+pub trait Vars {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>>;
+
+    /// Returns the value of a variable.
+    ///
+    /// Override this method to supply lists or associative arrays.
+    fn value(&mut self, index: usize, name: &str) -> Option<Value<'_>> {
+        self.var(index, name).map(Value::String)
+    }
+
+    /// Returns `true` if the value of a variable is already percent-encoded.
+    ///
+    /// Such values are decoded before they are expanded by expressions other than `{+var}` and `{#var}`,
+    /// which keep percent-encoded triplets as is.
+    fn is_encoded(&mut self, index: usize, name: &str) -> bool {
+        let _ = (index, name);
+        false
+    }
+}
+impl Vars for () {
+    fn var(&mut self, _index: usize, _name: &str) -> Option<Cow<'_, str>> {
+        None
     }
 }
-impl<K> Vars for &BTreeMap<K, String>
+impl<K, V> Vars for &HashMap<K, V>
 where
-    K: std::borrow::Borrow<str> + Ord,
+    K: std::borrow::Borrow<str> + Hash + Eq,
+    V: ToValue,
 {
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(Cow::Borrowed(self.get(name)?))
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        Some(self.get(name)?.to_value())
     }
 }
-impl<K> Vars for &BTreeMap<K, &dyn fmt::Display>
+impl<K, V> Vars for &BTreeMap<K, V>
 where
     K: std::borrow::Borrow<str> + Ord,
+    V: ToValue,
 {
-    fn var(&mut self, _index: usize, name: &str) -> Option<Cow<'_, str>> {
-        Some(self.get(name)?.to_string().into())
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        Some(self.get(name)?.to_value())
     }
 }
 
@@ -71,3 +157,141 @@ impl Vars for &[&dyn fmt::Display] {
         Some(Cow::Owned(self.get(index)?.to_string()))
     }
 }
+impl Vars for &[Value<'_>] {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, index: usize, _name: &str) -> Option<Value<'_>> {
+        Some(self.get(index)?.to_value())
+    }
+}
+
+/// Uses the captured values as variables.
+///
+/// Values captured by `{+var}` and `{#var}` keep their percent-encoding,
+/// so that expanding them again with `{+var}` or `{#var}` gives the same text,
+/// and are decoded when expanded with other operators.
+impl Vars for &Captures<'_> {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        self.name(name)?.to_value()
+    }
+    fn is_encoded(&mut self, _index: usize, name: &str) -> bool {
+        self.name(name).is_some_and(|m| m.is_encoded())
+    }
+}
+
+/// Same as the implementation for [`&Captures`](Captures).
+impl Vars for &OwnedCaptures {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        self.name(name)?.as_match().to_value()
+    }
+    fn is_encoded(&mut self, _index: usize, name: &str) -> bool {
+        self.name(name).is_some_and(|m| m.as_match().is_encoded())
+    }
+}
+
+/// Variables created by [`from_serialize`].
+#[derive(Clone, Debug)]
+pub struct SerializeVars(serde_json::Map<String, serde_json::Value>);
+
+/// Creates variables from the fields of a struct or the entries of a map.
+///
+/// Numbers and booleans are converted to strings, `None` and `()` are treated as undefined,
+/// sequences become lists and nested structs and maps become associative arrays.
+///
+/// ```
+/// use serde::Serialize;
+/// use uri_template_ex::{UriTemplate, vars};
+///
+/// #[derive(Serialize)]
+/// struct Query {
+///     q: String,
+///     page: u32,
+///     lang: Option<String>,
+/// }
+///
+/// let template = UriTemplate::new("/search{?q,page,lang}")?;
+/// let query = Query { q: "rust".into(), page: 2, lang: None };
+/// assert_eq!(template.expand(&vars::from_serialize(&query)?), "/search?q=rust&page=2");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<SerializeVars> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::Object(map) => Ok(SerializeVars(map)),
+        _ => Err(serde::ser::Error::custom("expected a struct or a map")),
+    }
+}
+
+impl Vars for &SerializeVars {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        json_to_value(self.0.get(name)?)
+    }
+}
+
+/// Uses the members of a JSON object as variables.
+///
+/// `null` is undefined, numbers and booleans are converted to strings,
+/// arrays are lists and objects are associative arrays.
+impl Vars for &serde_json::Map<String, serde_json::Value> {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        json_to_value(self.get(name)?)
+    }
+}
+
+/// Uses the members of a JSON object as variables.
+///
+/// If the value is not an object, all variables are undefined.
+impl Vars for &serde_json::Value {
+    fn var(&mut self, index: usize, name: &str) -> Option<Cow<'_, str>> {
+        into_string(self.value(index, name)?)
+    }
+    fn value(&mut self, _index: usize, name: &str) -> Option<Value<'_>> {
+        json_to_value(self.get(name)?)
+    }
+}
+
+/// Converts a JSON value to a variable value. `null` is undefined.
+fn json_to_value(value: &serde_json::Value) -> Option<Value<'_>> {
+    use serde_json::Value as Json;
+    match value {
+        Json::Null => None,
+        Json::Array(items) => Some(Value::List(items.iter().filter_map(json_to_str).collect())),
+        Json::Object(map) => Some(Value::AssocArray(
+            map.iter()
+                .filter_map(|(k, v)| Some((Cow::Borrowed(k.as_str()), json_to_str(v)?)))
+                .collect(),
+        )),
+        _ => json_to_str(value).map(Value::String),
+    }
+}
+
+/// Converts a JSON value to a string. Nested arrays and objects are written as JSON.
+fn json_to_str(value: &serde_json::Value) -> Option<Cow<'_, str>> {
+    use serde_json::Value as Json;
+    match value {
+        Json::Null => None,
+        Json::String(s) => Some(Cow::Borrowed(s)),
+        Json::Bool(_) | Json::Number(_) | Json::Array(_) | Json::Object(_) => {
+            Some(Cow::Owned(value.to_string()))
+        }
+    }
+}
+
+pub(crate) fn into_string(value: Value) -> Option<Cow<str>> {
+    match value {
+        Value::String(s) => Some(s),
+        Value::List(_) | Value::AssocArray(_) => None,
+    }
+}
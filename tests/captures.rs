@@ -1,4 +1,5 @@
-use uri_template_ex::Captures;
+use std::collections::BTreeMap;
+use uri_template_ex::{Captures, UriTemplate};
 
 #[test]
 fn captures_empty() {
@@ -9,3 +10,328 @@ fn captures_empty() {
     assert!(empty.name("a").is_none());
     assert!(empty.get(0).is_none());
 }
+
+#[track_caller]
+fn values(template: &str, input: &str, name: &str) -> Vec<String> {
+    let template = UriTemplate::new(template).unwrap();
+    let captures = template.captures(input).unwrap();
+    let m = captures.name(name).unwrap();
+    m.values()
+        .unwrap()
+        .into_iter()
+        .map(|s| s.into_owned())
+        .collect()
+}
+
+#[track_caller]
+fn pairs(template: &str, input: &str, name: &str) -> Vec<(String, String)> {
+    let template = UriTemplate::new(template).unwrap();
+    let captures = template.captures(input).unwrap();
+    let m = captures.name(name).unwrap();
+    m.pairs()
+        .unwrap()
+        .into_iter()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect()
+}
+
+#[test]
+fn captures_values() {
+    assert_eq!(
+        values("/files{/path*}", "/files/a/b%20c", "path"),
+        ["a", "b c"]
+    );
+    assert_eq!(values("/files{/path*}", "/files/a", "path"), ["a"]);
+    assert_eq!(
+        values("{list}", "red,green,blue", "list"),
+        ["red", "green", "blue"]
+    );
+    assert_eq!(values("X{.list*}", "X.red.green", "list"), ["red", "green"]);
+    assert_eq!(
+        values("{;list*}", ";list=red;list=green;list", "list"),
+        ["red", "green", ""]
+    );
+    assert_eq!(
+        values("{?list*}", "?list=red&list=green", "list"),
+        ["red", "green"]
+    );
+    assert_eq!(
+        values("{?list}", "?list=red,green", "list"),
+        ["red", "green"]
+    );
+    assert_eq!(values("{+list}", "a%2F,b/c", "list"), ["a%2F", "b/c"]);
+}
+
+#[test]
+fn captures_pairs() {
+    assert_eq!(
+        pairs("/search{?filters*}", "/search?a=1&b=x%20y", "filters"),
+        [("a".into(), "1".into()), ("b".into(), "x y".into())]
+    );
+    assert_eq!(
+        pairs("{?keys}", "?keys=semi,%3B,dot,.", "keys"),
+        [("semi".into(), ";".into()), ("dot".into(), ".".into())]
+    );
+    assert_eq!(
+        pairs("{;keys*}", ";semi=%3B;empty", "keys"),
+        [("semi".into(), ";".into()), ("empty".into(), "".into())]
+    );
+    assert_eq!(
+        pairs("{keys*}", "semi=%3B,dot=.", "keys"),
+        [("semi".into(), ";".into()), ("dot".into(), ".".into())]
+    );
+}
+
+#[test]
+fn captures_exploded_value() -> uri_template_ex::Result<()> {
+    let template = UriTemplate::new("{?list*}")?;
+    let captures = template.captures("?list=red&list=green").unwrap();
+    assert_eq!(captures.name("list").unwrap().value()?, "red,green");
+    Ok(())
+}
+
+#[test]
+fn captures_repeated_var() -> uri_template_ex::Result<()> {
+    let template = UriTemplate::new("/{id}/copy-of/{id}")?;
+    let captures = template.captures("/a%20b/copy-of/a%20b").unwrap();
+    assert_eq!(captures.name("id").unwrap().value()?, "a b");
+    let all: Vec<_> = captures.all("id").map(|m| m.start()).collect();
+    assert_eq!(all, [1, 15]);
+    assert!(template.captures("/a/copy-of/b").is_none());
+
+    let template = UriTemplate::new("/{id}{?id}")?;
+    assert!(template.captures("/a%2Fb?id=a%2Fb").is_some());
+    assert!(template.captures("/a?id=b").is_none());
+
+    let template = UriTemplate::new("/{id}/{+id}")?;
+    assert!(template.captures("/a%2Fb/a/b").is_some());
+    Ok(())
+}
+
+#[track_caller]
+fn check_round_trip(template: &str, vars: &[(&str, &str)], expected: &str) {
+    let template = UriTemplate::new(template).unwrap();
+    let vars: BTreeMap<_, _> = vars.iter().copied().collect();
+    let uri = template.expand(&vars);
+    assert_eq!(uri, expected);
+    let captures = template.captures(&uri).unwrap();
+    for (name, value) in &vars {
+        assert_eq!(captures.name(name).unwrap().value().unwrap(), *value);
+    }
+}
+
+#[test]
+fn captures_repeated_var_round_trip() -> uri_template_ex::Result<()> {
+    check_round_trip("{/var:1,var}", &[("var", "value")], "/v/value");
+    check_round_trip("{x}{x}", &[("x", "a")], "aa");
+    check_round_trip("{x}-{y}-{x}", &[("x", "a"), ("y", "b-c")], "a-b-c-a");
+
+    let template = UriTemplate::new("{/var:1,var}")?;
+    let captures = template.captures("/v/value").unwrap();
+    let all: Vec<_> = captures.all("var").map(|m| m.source()).collect();
+    assert_eq!(all, ["v", "value"]);
+    assert!(template.captures("/x/value").is_none());
+    assert!(template.captures("/va/value").is_none());
+    let owned = captures.into_owned();
+    assert_eq!(owned.name("var").unwrap().value()?, "value");
+
+    let template = UriTemplate::new("{x:2}{x}")?;
+    assert_eq!(template.expand(&BTreeMap::from([("x", "abc")])), "ababc");
+    let captures = template.captures("ababc").unwrap();
+    assert_eq!(captures.name("x").unwrap().value()?, "abc");
+    assert!(template.captures("abxyz").is_none());
+
+    let template = UriTemplate::new("/{a}{+b}/{a}")?;
+    let captures = template.captures("/ab/cd/ab").unwrap();
+    assert_eq!(captures.name("b").unwrap().source(), "/cd");
+    let input = format!("/{}/b", "a".repeat(100_000));
+    assert!(template.captures(&input).is_none());
+
+    let template = UriTemplate::new("/{id:3}/{id}")?;
+    let captures = template.captures("/abc/abcdef").unwrap();
+    assert_eq!(captures.name("id").unwrap().value()?, "abcdef");
+    let map = BTreeMap::from(captures.into_owned());
+    assert_eq!(
+        map,
+        BTreeMap::from([("id".to_string(), "abcdef".to_string())])
+    );
+    Ok(())
+}
+
+#[test]
+fn captures_parse() -> uri_template_ex::Result<()> {
+    use uri_template_ex::ParseErrorKind;
+
+    let template = UriTemplate::new("/users/{id}/files/{name}{?page}")?;
+    let captures = template.captures("/users/42/files/a%20b").unwrap();
+    assert_eq!(captures.parse::<u64>("id").unwrap(), 42);
+    assert_eq!(captures.name("id").unwrap().parse::<u64>().unwrap(), 42);
+    assert_eq!(captures.parse::<String>("name").unwrap(), "a b");
+
+    let e = captures.parse::<u64>("name").unwrap_err();
+    assert_eq!(e.name(), "name");
+    assert_eq!(e.span(), Some(16..21));
+    assert!(matches!(e.kind(), ParseErrorKind::Parse(_)));
+    assert_eq!(
+        e.to_string(),
+        "variable `name` at 16..21: invalid digit found in string"
+    );
+
+    let e = captures.parse::<u64>("page").unwrap_err();
+    assert_eq!(e.span(), None);
+    assert!(matches!(e.kind(), ParseErrorKind::NotCaptured));
+    assert_eq!(e.to_string(), "variable `page` is not captured");
+
+    let captures = template.captures("/users/%FF/files/x").unwrap();
+    let e = captures.parse::<String>("id").unwrap_err();
+    assert_eq!(e.span(), Some(7..10));
+    assert!(matches!(e.kind(), ParseErrorKind::Decode(_)));
+    Ok(())
+}
+
+#[test]
+fn captures_into_owned() -> uri_template_ex::Result<()> {
+    use uri_template_ex::OwnedCaptures;
+
+    fn owned(input: &str) -> OwnedCaptures {
+        let template = UriTemplate::new("/users/{id}{/path*}{?q,page}").unwrap();
+        template.captures(input).unwrap().into_owned()
+    }
+    let captures = owned("/users/a%20b/x/y?page=2");
+    let id = captures.name("id").unwrap();
+    assert_eq!(id.value()?, "a b");
+    assert_eq!(id.source(), "a%20b");
+    assert_eq!(id.span(), 7..12);
+    assert_eq!(captures.name("path").unwrap().values()?, ["x", "y"]);
+    assert_eq!(captures.parse::<u32>("page").unwrap(), 2);
+    assert!(captures.name("q").is_none());
+    let names: Vec<_> = captures.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["id", "path", "q", "page"]);
+
+    let map = BTreeMap::from(captures);
+    assert_eq!(
+        map,
+        BTreeMap::from([
+            ("id".to_string(), "a b".to_string()),
+            ("page".to_string(), "2".to_string()),
+            ("path".to_string(), "x,y".to_string()),
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn captures_as_vars() -> uri_template_ex::Result<()> {
+    let from = UriTemplate::new("/v1/users/{id}/files/{+path}")?;
+    let to = UriTemplate::new("/v2/files/{+path}{?id}")?;
+    let captures = from.captures("/v1/users/a%20b/files/x%2Fy/z%20w").unwrap();
+    let expected = "/v2/files/x%2Fy/z%20w?id=a%20b";
+    assert_eq!(to.expand(&captures), expected);
+    assert_eq!(to.expand(&captures.into_owned()), expected);
+
+    let from = UriTemplate::new("/items{?q,tags*}")?;
+    let to = UriTemplate::new("/v2/items{?tags*,q}")?;
+    let captures = from.captures("/items?q=%26&tags=red&tags=blue").unwrap();
+    assert_eq!(to.expand(&captures), "/v2/items?tags=red&tags=blue&q=%26");
+
+    let from = UriTemplate::new("/search{?filters*}")?;
+    let to = UriTemplate::new("/find{;filters*}")?;
+    let captures = from.captures("/search?a=1&b=x%20y").unwrap();
+    assert_eq!(to.expand(&captures), "/find;a=1;b=x%20y");
+
+    let from = UriTemplate::new("/files{/path*}")?;
+    let to = UriTemplate::new("{#path*}")?;
+    let captures = from.captures("/files/a/b").unwrap();
+    assert_eq!(to.expand(&captures), "#a,b");
+    Ok(())
+}
+
+#[test]
+fn captures_as_vars_with_other_operator() -> uri_template_ex::Result<()> {
+    let from = UriTemplate::new("/old/{+rest}")?;
+    let captures = from.captures("/old/a%20b/c").unwrap();
+    let to = UriTemplate::new("/new/{rest}")?;
+    assert_eq!(to.expand(&captures), "/new/a%20b%2Fc");
+    assert_eq!(to.expand(&captures.into_owned()), "/new/a%20b%2Fc");
+
+    let captures = from.captures("/old/x%2Fy%e3%81%82").unwrap();
+    let to = UriTemplate::new("/new{?rest}")?;
+    assert_eq!(to.expand(&captures), "/new?rest=x%2Fy%E3%81%82");
+    let to = UriTemplate::new("/new/{rest:2}")?;
+    assert_eq!(to.expand(&captures), "/new/x%2F");
+
+    let from = UriTemplate::new("/a{#list*}")?;
+    let to = UriTemplate::new("/b{/list*}")?;
+    let captures = from.captures("/a#x%20y,z/w").unwrap();
+    assert_eq!(to.expand(&captures), "/b/x%20y/z%2Fw");
+    Ok(())
+}
+
+#[test]
+fn captures_prefix() {
+    let t = UriTemplate::new("/tenants/{tenant}").unwrap();
+    let (c, rest) = t.captures_prefix("/tenants/acme/users/1").unwrap();
+    assert_eq!(c.name("tenant").unwrap().value().unwrap(), "acme");
+    assert_eq!(rest, "/users/1");
+
+    let (c, rest) = t.captures_prefix("/tenants/acme").unwrap();
+    assert_eq!(c.name("tenant").unwrap().value().unwrap(), "acme");
+    assert_eq!(rest, "");
+
+    let (_, rest) = t.captures_prefix("/tenants/acme?page=2").unwrap();
+    assert_eq!(rest, "?page=2");
+    let (_, rest) = t.captures_prefix("/tenants/acme#top").unwrap();
+    assert_eq!(rest, "#top");
+
+    assert!(t.captures_prefix("/tenant/acme").is_none());
+    assert!(t.captures_prefix("/other/tenants/acme").is_none());
+}
+
+#[test]
+fn captures_prefix_segment_boundary() {
+    let t = UriTemplate::new("/api").unwrap();
+    assert_eq!(t.captures_prefix("/api/users").unwrap().1, "/users");
+    assert_eq!(t.captures_prefix("/api").unwrap().1, "");
+    assert!(t.captures_prefix("/apiary").is_none());
+
+    let t = UriTemplate::new("/api/").unwrap();
+    assert_eq!(t.captures_prefix("/api/users").unwrap().1, "users");
+
+    let t = UriTemplate::new("/files{/path*}").unwrap();
+    let (c, rest) = t.captures_prefix("/files/a/b?x=1").unwrap();
+    assert_eq!(c.name("path").unwrap().values().unwrap(), ["a", "b"]);
+    assert_eq!(rest, "?x=1");
+}
+
+#[test]
+fn captures_prefix_query() {
+    let t = UriTemplate::new("/a{?x}").unwrap();
+    let (c, rest) = t.captures_prefix("/a?x=1&y=2").unwrap();
+    assert_eq!(c.name("x").unwrap().value().unwrap(), "1");
+    assert_eq!(rest, "&y=2");
+    let (c, rest) = t.captures_prefix("/a?y=2").unwrap();
+    assert!(c.name("x").is_none());
+    assert_eq!(rest, "?y=2");
+    assert!(t.captures_prefix("/a&y=2").is_none());
+
+    let t = UriTemplate::new("/a?v=1{&x}").unwrap();
+    let (c, rest) = t.captures_prefix("/a?v=1&x=2&y=3").unwrap();
+    assert_eq!(c.name("x").unwrap().value().unwrap(), "2");
+    assert_eq!(rest, "&y=3");
+    let (c, rest) = t.captures_prefix("/a?v=1&y=3").unwrap();
+    assert!(c.name("x").is_none());
+    assert_eq!(rest, "&y=3");
+
+    let t = UriTemplate::new("/a/{x}").unwrap();
+    assert!(t.captures_prefix("/a/b&c").is_none());
+}
+
+#[test]
+fn captures_prefix_nested() {
+    let outer = UriTemplate::new("/tenants/{tenant}").unwrap();
+    let inner = UriTemplate::new("/users/{id}").unwrap();
+    let (c0, rest) = outer.captures_prefix("/tenants/acme/users/42").unwrap();
+    let c1 = inner.captures(rest).unwrap();
+    assert_eq!(c0.name("tenant").unwrap().value().unwrap(), "acme");
+    assert_eq!(c1.name("id").unwrap().value().unwrap(), "42");
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use uri_template_ex::UriTemplate;
+
+#[test]
+fn deserialize_struct() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Params<'a> {
+        user: &'a str,
+        id: u64,
+        name: String,
+        frag: Option<String>,
+    }
+    let template = UriTemplate::new("/users/{user}/files/{id}/{name}{#frag}").unwrap();
+    let captures = template.captures("/users/alice/files/42/a%20b").unwrap();
+    assert_eq!(
+        captures.deserialize::<Params>().unwrap(),
+        Params {
+            user: "alice",
+            id: 42,
+            name: "a b".into(),
+            frag: None,
+        }
+    );
+    let captures = template.captures("/users/alice/files/42/x#top").unwrap();
+    let params: Params = captures.deserialize().unwrap();
+    assert_eq!(params.frag.as_deref(), Some("top"));
+}
+
+#[test]
+fn deserialize_composite() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Params {
+        path: Vec<u32>,
+        filters: BTreeMap<String, bool>,
+    }
+    let template = UriTemplate::new("/files{/path*}{?filters*}").unwrap();
+    let captures = template.captures("/files/1/2/3?a=true&b=false").unwrap();
+    assert_eq!(
+        captures.deserialize::<Params>().unwrap(),
+        Params {
+            path: vec![1, 2, 3],
+            filters: [("a".into(), true), ("b".into(), false)].into(),
+        }
+    );
+}
+
+#[test]
+fn deserialize_repeated_var() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Params {
+        id: String,
+    }
+    let template = UriTemplate::new("/{id:3}/{id}").unwrap();
+    let captures = template.captures("/abc/abcdef").unwrap();
+    assert_eq!(
+        captures.deserialize::<Params>().unwrap(),
+        Params {
+            id: "abcdef".into()
+        }
+    );
+}
+
+#[test]
+fn deserialize_enum() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Format {
+        Json,
+        Xml,
+    }
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Params {
+        format: Format,
+    }
+    let template = UriTemplate::new("/data{.format}").unwrap();
+    let captures = template.captures("/data.xml").unwrap();
+    assert_eq!(
+        captures.deserialize::<Params>().unwrap(),
+        Params {
+            format: Format::Xml
+        }
+    );
+}
+
+#[test]
+fn deserialize_error() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Params {
+        user: String,
+        id: u64,
+    }
+    let template = UriTemplate::new("/users/{user}/{id}{#x}").unwrap();
+    let captures = template.captures("/users/alice/abc").unwrap();
+    let e = captures.deserialize::<Params>().unwrap_err();
+    assert_eq!(e.var(), Some("id"));
+    assert!(
+        e.to_string()
+            .starts_with("variable `id`: cannot parse `abc`")
+    );
+
+    let template = UriTemplate::new("/users/{user}{#id}").unwrap();
+    let captures = template.captures("/users/alice").unwrap();
+    let e = captures.deserialize::<Params>().unwrap_err();
+    assert_eq!(e.var(), Some("id"));
+    assert_eq!(e.to_string(), "variable `id`: missing field `id`");
+}
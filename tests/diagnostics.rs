@@ -0,0 +1,67 @@
+use uri_template_ex::{ErrorKind, UriTemplate};
+
+#[track_caller]
+fn check(template: &str, expected: &[(ErrorKind, std::ops::Range<usize>)]) {
+    let diagnostics = UriTemplate::parse_with_diagnostics(template).unwrap_err();
+    let actual: Vec<_> = diagnostics.iter().map(|d| (d.kind(), d.span())).collect();
+    assert_eq!(actual, expected, "template = `{template}`");
+}
+
+#[test]
+fn valid() {
+    assert!(UriTemplate::parse_with_diagnostics("/users/{id}{?q,page}").is_ok());
+}
+
+#[test]
+fn reports_all_errors() {
+    check(
+        "/a/{b c}/{}/{d:0}}",
+        &[
+            (ErrorKind::InvalidVarName, 4..7),
+            (ErrorKind::EmptyExpression, 9..11),
+            (ErrorKind::InvalidModifier, 14..16),
+            (ErrorKind::UnexpectedCloseBrace, 17..18),
+        ],
+    );
+    check(
+        "{a b,c d}",
+        &[
+            (ErrorKind::InvalidVarName, 1..4),
+            (ErrorKind::InvalidVarName, 5..8),
+        ],
+    );
+    check(
+        "{!a}/{x",
+        &[
+            (ErrorKind::UnknownOperator, 1..2),
+            (ErrorKind::UnclosedExpression, 5..7),
+        ],
+    );
+    check("{a{b}/{c}", &[(ErrorKind::NestedBrace, 2..3)]);
+}
+
+#[test]
+fn message_and_suggestion() {
+    let diagnostics = UriTemplate::parse_with_diagnostics("/{default-graph-uri}").unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!(d.message(), "invalid variable name `default-graph-uri`");
+    assert_eq!(d.suggestion(), Some("use `default%2Dgraph%2Duri`"));
+    assert_eq!(d.to_string(), d.message());
+
+    let diagnostics = UriTemplate::parse_with_diagnostics("{hello:2*}").unwrap_err();
+    assert_eq!(
+        diagnostics[0].suggestion(),
+        Some("prefix and explode modifiers cannot be combined")
+    );
+}
+
+#[test]
+fn same_first_error_as_new() {
+    for template in ["{a", "a}", "{}", "{a b}{c d}", "{=a}", "{a:0}"] {
+        let e = UriTemplate::new(template).unwrap_err();
+        let diagnostics = UriTemplate::parse_with_diagnostics(template).unwrap_err();
+        assert_eq!(e.kind(), diagnostics[0].kind(), "template = `{template}`");
+        assert_eq!(e.span(), diagnostics[0].span(), "template = `{template}`");
+    }
+}
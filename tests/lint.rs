@@ -0,0 +1,44 @@
+use uri_template_ex::{LintKind, UriTemplate};
+
+#[track_caller]
+fn check(template: &str, expected: &[(LintKind, std::ops::Range<usize>)]) {
+    let lints = UriTemplate::new(template).unwrap().lint();
+    let actual: Vec<_> = lints.iter().map(|l| (l.kind(), l.span())).collect();
+    assert_eq!(actual, expected, "template = `{template}`");
+}
+
+#[test]
+fn no_lints() {
+    check("/users/{id}{?q,page}", &[]);
+    check("/files/{+path}", &[]);
+    check("/files/{+path}/edit", &[]);
+    check("{/a,b}{?c*}", &[]);
+    check("/{a}/{a}", &[]);
+}
+
+#[test]
+fn ambiguous_capture() {
+    check("{a}{b}", &[(LintKind::AmbiguousCapture, 0..6)]);
+    check("/{id}{.format}", &[(LintKind::AmbiguousCapture, 1..14)]);
+    check("{/a*}{/b}", &[(LintKind::AmbiguousCapture, 0..9)]);
+    check("{/a*,b}", &[(LintKind::AmbiguousCapture, 2..4)]);
+    check("{+a}{?b}", &[(LintKind::AmbiguousCapture, 0..8)]);
+}
+
+#[test]
+fn inconsistent_operator() {
+    check("/{a}{?a}", &[(LintKind::InconsistentOperator, 6..7)]);
+    check("/{a}/{a:3}", &[(LintKind::InconsistentOperator, 6..9)]);
+    let lints = UriTemplate::new("/{a}{?a}").unwrap().lint();
+    assert_eq!(
+        lints[0].message(),
+        "variable `a` is used as `{a}` and `{?a}`"
+    );
+}
+
+#[test]
+fn reserved_swallows_literal() {
+    check("/{+x}/{+y}", &[(LintKind::ReservedSwallowsLiteral, 1..6)]);
+    check("{#a}.{b}", &[(LintKind::ReservedSwallowsLiteral, 0..5)]);
+    check("/{+x}/a/{y}", &[(LintKind::ReservedSwallowsLiteral, 1..8)]);
+}
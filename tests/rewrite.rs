@@ -0,0 +1,98 @@
+use uri_template_ex::{ErrorKind, RewriteRule, RewriteRules, SkipReason, UriTemplate};
+
+fn rule(from: &str, to: &str) -> RewriteRule {
+    RewriteRule::new(
+        UriTemplate::new(from).unwrap(),
+        UriTemplate::new(to).unwrap(),
+    )
+}
+
+#[test]
+fn apply() {
+    let rules: RewriteRules = [
+        rule("/old/users/{id}", "/users/{id}"),
+        rule("/old/files{/path*}", "/files{/path*}"),
+        rule("/blog/{+rest}", "https://blog.example.com/{+rest}"),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(rules.len(), 3);
+    assert_eq!(rules.apply("/old/users/alice").unwrap(), "/users/alice");
+    assert_eq!(rules.apply("/old/files/a/b%20c").unwrap(), "/files/a/b%20c");
+    assert_eq!(
+        rules.apply("/blog/2024/01/post?x=1").unwrap(),
+        "https://blog.example.com/2024/01/post?x=1"
+    );
+    assert_eq!(rules.apply("/other"), None);
+
+    let rules = RewriteRules::new([rule("/old/{+rest}", "/new/{rest}")]);
+    assert_eq!(rules.apply("/old/a%20b/c").unwrap(), "/new/a%20b%2Fc");
+}
+
+#[test]
+fn first_matching_rule() {
+    let rules = RewriteRules::new([rule("/a/{x}", "/first/{x}"), rule("/a/{y}", "/second/{y}")]);
+    assert_eq!(rules.apply("/a/1").unwrap(), "/first/1");
+}
+
+#[test]
+fn rename_and_default() {
+    let rules = RewriteRules::new([rule("/u/{name}{?tab}", "/users/{user}/{tab}")
+        .rename("name", "user")
+        .default("tab", "profile")]);
+    assert_eq!(
+        rules.apply("/u/alice?tab=posts").unwrap(),
+        "/users/alice/posts"
+    );
+    assert_eq!(rules.apply("/u/alice").unwrap(), "/users/alice/profile");
+}
+
+#[test]
+fn undefined_var() {
+    let rules = RewriteRules::new([rule("/old{?q}", "/new{?q}")]);
+    assert_eq!(rules.apply("/old").unwrap(), "/new");
+    assert_eq!(rules.apply("/old?q=x").unwrap(), "/new?q=x");
+
+    let rule = rule("/old{?q}", "/new{?q}").require("q");
+    assert!(matches!(rule.apply("/old"), Err(SkipReason::MissingVar(name)) if name == "q"));
+    assert_eq!(rule.apply("/old?q=x").unwrap(), "/new?q=x");
+}
+
+#[test]
+fn report() {
+    let rules = RewriteRules::new([
+        rule("/a/{x}", "/x/{x}"),
+        rule("/b{/xs*}", "/b/{xs:1}"),
+        rule("/b/{+rest}", "/c/{+rest}"),
+    ]);
+    let r = rules.rewrite("/b/1/2");
+    assert_eq!(r.uri(), Some("/c/1/2"));
+    assert_eq!(r.rule(), Some(2));
+    let skipped = r.skipped();
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(skipped[0].0, 0);
+    assert!(matches!(skipped[0].1, SkipReason::NotMatched));
+    assert_eq!(skipped[1].0, 1);
+    assert!(
+        matches!(&skipped[1].1, SkipReason::Expand(e) if e.kind() == ErrorKind::PrefixOnCompositeValue)
+    );
+
+    let r = rules.rewrite("/z");
+    assert_eq!(r.uri(), None);
+    assert_eq!(r.rule(), None);
+    assert_eq!(r.skipped().len(), 3);
+    assert_eq!(r.skipped()[0].1.to_string(), "not matched");
+
+    let rules = RewriteRules::new([
+        rule("/u/{name}", "/users/{user}/{tab}"),
+        rule("/u/{name}", "/users/{user}/{tab}").rename("name", "user"),
+        rule("/u/{name}", "/users/{name}"),
+    ]);
+    let r = rules.rewrite("/u/alice");
+    assert_eq!(r.uri(), Some("/users/alice"));
+    assert_eq!(r.rule(), Some(2));
+    let skipped = r.skipped();
+    assert!(matches!(&skipped[0].1, SkipReason::MissingVar(name) if name == "user"));
+    assert!(matches!(&skipped[1].1, SkipReason::MissingVar(name) if name == "tab"));
+    assert_eq!(skipped[1].1.to_string(), "variable `tab` has no value");
+}
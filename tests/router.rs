@@ -0,0 +1,63 @@
+use uri_template_ex::{Router, UriTemplate};
+
+fn build(templates: &[&str]) -> Router<usize> {
+    Router::new(
+        templates
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (UriTemplate::new(t).unwrap(), i)),
+    )
+    .unwrap()
+}
+
+#[test]
+fn find() {
+    let router = build(&[
+        "/users/{id}",
+        "/users/{id}/files/{+path}",
+        "/search{?q,page}",
+    ]);
+    assert_eq!(router.len(), 3);
+
+    let (value, captures) = router.find("/users/alice").unwrap();
+    assert_eq!(*value, 0);
+    assert_eq!(captures.name("id").unwrap().value().unwrap(), "alice");
+
+    let (value, captures) = router.find("/users/alice/files/a/b.txt").unwrap();
+    assert_eq!(*value, 1);
+    assert_eq!(captures.name("path").unwrap().value().unwrap(), "a/b.txt");
+
+    let (value, captures) = router.find("/search?q=rust&page=2").unwrap();
+    assert_eq!(*value, 2);
+    assert_eq!(captures.name("page").unwrap().value().unwrap(), "2");
+
+    assert!(router.find("/unknown").is_none());
+}
+
+#[test]
+fn priority() {
+    let router = build(&["/files/{+path}", "/files/{name}", "/files/readme"]);
+    let values: Vec<_> = router.find_all("/files/readme").map(|(v, _)| *v).collect();
+    assert_eq!(values, [2, 1, 0]);
+    assert_eq!(*router.find("/files/readme").unwrap().0, 2);
+    assert_eq!(*router.find("/files/a").unwrap().0, 1);
+    assert_eq!(*router.find("/files/a/b").unwrap().0, 0);
+
+    let users = build(&["/users/{id}", "/users/me"]);
+    assert_eq!(*users.find("/users/me").unwrap().0, 1);
+    assert_eq!(*users.find("/users/you").unwrap().0, 0);
+}
+
+#[test]
+fn priority_same_specificity() {
+    let router = build(&["/{a}/x", "/x/{b}"]);
+    let values: Vec<_> = router.find_all("/x/x").map(|(v, _)| *v).collect();
+    assert_eq!(values, [0, 1]);
+}
+
+#[test]
+fn empty() {
+    let router = build(&[]);
+    assert!(router.is_empty());
+    assert!(router.find("/").is_none());
+}
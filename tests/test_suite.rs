@@ -1,8 +1,10 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
 use std::path::PathBuf;
-use uri_template_ex::UriTemplate;
+use uri_template_ex::{UriTemplate, Value};
 
 fn load_test_suite(file_name: &str) -> TestSuite {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -15,64 +17,21 @@ fn load_test_suite(file_name: &str) -> TestSuite {
     serde_json::from_str(&json).unwrap_or_else(|e| panic!("Failed to parse {file_name}: {e}"))
 }
 
-/// Guess the level from the section name
-#[allow(clippy::if_same_then_else)]
-fn guess_level(section_name: &str) -> u8 {
-    if section_name.contains("Level 1") || section_name.contains("3.2.2") {
-        1
-    } else if section_name.contains("Level 2") || section_name.contains("3.2.3") {
-        2
-    } else if section_name.contains("Level 3")
-        || section_name.contains("3.2.4")
-        || section_name.contains("3.2.5")
-        || section_name.contains("3.2.6")
-        || section_name.contains("3.2.9")
-    // Form-Style Query Continuation is Level 3
-    {
-        3
-    } else if section_name.contains("Level 4")
-        || section_name.contains("3.2.7")
-        || section_name.contains("3.2.8")
-    {
-        4
-    } else if section_name == "Failure Tests" {
-        4 // Failure Tests is treated as a special case
-    } else {
-        1 // Default to Level 1
-    }
-}
-
-/// Check if the value type is supported in Level 2
-fn is_level2_supported_value(value: &VariableValue) -> bool {
-    matches!(
-        value,
-        VariableValue::String(_) | VariableValue::Number(_) | VariableValue::Null
-    )
-}
-
-/// Extract variable names used in the template
-fn extract_variable_names(template: &str) -> Vec<String> {
-    let mut names = Vec::new();
-    let mut in_var = false;
-    let mut start = 0;
-
-    for (i, c) in template.char_indices() {
-        match c {
-            '{' => {
-                in_var = true;
-                start = i + 1;
+fn to_vars(variables: &HashMap<String, VariableValue>) -> BTreeMap<&str, Value<'_>> {
+    let mut vars = BTreeMap::new();
+    for (k, v) in variables {
+        let value = match v {
+            VariableValue::String(s) => Value::String(s.into()),
+            VariableValue::Number(n) => Value::String(n.to_string().into()),
+            VariableValue::Array(items) => Value::List(items.iter().map(|s| s.into()).collect()),
+            VariableValue::Object(items) => {
+                Value::AssocArray(items.0.iter().map(|(k, v)| (k.into(), v.into())).collect())
             }
-            '}' if in_var => {
-                let var_part = &template[start..i];
-                // Remove modifiers (+, #, ., /, ;, ?, &)
-                let name = var_part.trim_start_matches(|c| "#+.;/?&".contains(c));
-                names.push(name.to_string());
-                in_var = false;
-            }
-            _ => {}
-        }
+            VariableValue::Null => continue,
+        };
+        vars.insert(k.as_str(), value);
     }
-    names
+    vars
 }
 
 #[test]
@@ -89,107 +48,42 @@ fn check_all_test_suite() {
         println!("Testing {file_name}");
 
         for (section_name, section) in test_suite.0.iter() {
-            // Determine the section level
-            let level = if section.level > 0 {
+            println!(
+                "  Testing section: {section_name} (level {})",
                 section.level
-            } else {
-                guess_level(section_name)
-            };
-
-            // Skip tests for level 3 and above
-            if level > 2 {
-                println!("Skipping {section_name} (level {level})");
-                continue;
-            }
-
-            println!("  Testing section: {section_name} (level {level})");
+            );
+            let vars = to_vars(&section.variables);
             for test in &section.testcases {
-                let template = match UriTemplate::new(&test.template) {
-                    Ok(t) => t,
-                    Err(e) => {
-                        // For Failure Tests section, errors are expected
-                        if section_name == "Failure Tests"
-                            && let ExpectedValue::Bool(false) = test.expected
-                        {
-                            // If an error is expected and actually occurs, it's a success
-                            continue;
-                        }
-                        panic!("Failed to parse template '{}': {}", test.template, e);
-                    }
-                };
-
-                // Skip templates using features beyond Level 2
-                if template.to_string().contains(',') || // Multiple variable expansion (Level 3)
-                   template.to_string().contains('*') || // Variable expansion modifier (Level 4)
-                   template.to_string().contains(':') || // Prefix modifier (Level 4)
-                   template.to_string().contains('?') || // Query parameter expansion (Level 3)
-                   template.to_string().contains('&') || // Query parameter continuation (Level 3)
-                   template.to_string().contains(';') || // Semicolon-prefixed parameters (Level 3)
-                   template.to_string().contains('#') || // Fragment identifier (Level 3)
-                   template.to_string().contains('.')
-                // Dot-prefixed labels (Level 3)
-                {
-                    println!(
-                        "    Skipping template: {} (requires level > 2)",
-                        test.template
-                    );
-                    continue;
-                }
-
-                // Check only variables used in the template
-                let var_names = extract_variable_names(&test.template);
-                let has_unsupported_var = var_names.iter().any(|name| {
-                    if let Some(value) = section.variables.get(name) {
-                        !is_level2_supported_value(value)
-                    } else {
-                        false // Undefined variables are allowed (treated as empty strings)
-                    }
-                });
-
-                if has_unsupported_var {
-                    println!(
-                        "    Skipping template: {} (uses unsupported variable types)",
-                        test.template
-                    );
-                    continue;
-                }
-
-                let mut vars = HashMap::new();
-                for (k, v) in section.variables.iter() {
-                    if var_names.contains(k) {
-                        let value = match v {
-                            VariableValue::String(s) => s.clone(),
-                            VariableValue::Number(n) => n.to_string(),
-                            VariableValue::Null => String::new(),
-                            _ => unreachable!("Already filtered out unsupported types"),
-                        };
-                        vars.insert(k.clone(), value);
-                    }
-                }
-
-                let expanded = template.expand(&vars);
-
-                match &test.expected {
-                    ExpectedValue::String(expected) => {
+                let expanded = UriTemplate::new(&test.template).and_then(|t| t.try_expand(&vars));
+                match (&test.expected, expanded) {
+                    (ExpectedValue::String(expected), Ok(expanded)) => {
                         assert_eq!(
                             expanded, *expected,
                             "Template '{}' with variables {:#?} expanded to '{}', expected '{}'",
                             test.template, section.variables, expanded, expected
                         );
                     }
-                    ExpectedValue::Array(_) => {
-                        println!(
-                            "    Skipping template: {} (array result is not expected in level 2)",
-                            test.template
+                    (ExpectedValue::Array(expected), Ok(expanded)) => {
+                        // Associative arrays may be expanded in any order
+                        assert!(
+                            expected.contains(&expanded),
+                            "Template '{}' with variables {:#?} expanded to '{}', expected one of {:?}",
+                            test.template,
+                            section.variables,
+                            expanded,
+                            expected
                         );
-                        continue;
                     }
-                    ExpectedValue::Bool(expected) => {
-                        if *expected {
-                            // For success test cases, the expansion result should match the expected value
-                            // However, this case is not handled in the current implementation
-                            println!("Warning: Unhandled success test case: {}", test.template);
-                        }
+                    (ExpectedValue::Bool(false), Ok(expanded)) => {
+                        panic!(
+                            "Template '{}' expanded to '{}', expected an error",
+                            test.template, expanded
+                        );
+                    }
+                    (ExpectedValue::Bool(false), Err(_)) => {}
+                    (ExpectedValue::Bool(true), Ok(_)) => {}
+                    (_, Err(e)) => {
+                        panic!("Failed to expand template '{}': {}", test.template, e);
                     }
                 }
             }
@@ -229,7 +123,7 @@ pub enum VariableValue {
     /// Map with string keys and values
     /// Example: `"keys": { "semi": ";", "dot": ".", "comma": "," }`
     /// [spec-examples.json#L83](../external/uritemplate-test/spec-examples.json#L83)
-    Object(HashMap<String, String>),
+    Object(OrderedMap),
 
     /// Null value
     /// Example: `"undef": null`
@@ -237,6 +131,30 @@ pub enum VariableValue {
     Null,
 }
 
+/// Map that keeps the order of the keys in the JSON file
+#[derive(Debug, Serialize)]
+pub struct OrderedMap(pub Vec<(String, String)>);
+
+impl<'de> Deserialize<'de> for OrderedMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedMapVisitor;
+        impl<'de> Visitor<'de> for OrderedMapVisitor {
+            type Value = OrderedMap;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map with string values")
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = map.next_entry()? {
+                    items.push(item);
+                }
+                Ok(OrderedMap(items))
+            }
+        }
+        deserializer.deserialize_map(OrderedMapVisitor)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TestCase {
     #[serde(rename = "0")]
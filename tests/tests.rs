@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use uri_template_ex::{Error, UriTemplate};
+use uri_template_ex::{Error, ErrorKind, UriTemplate};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -54,6 +54,91 @@ fn no_var() -> Result<()> {
 fn err() {
     // invalid variable
     check_err("{aaa");
+
+    // invalid modifier
+    check_err("{var:0}");
+    check_err("{var:01}");
+    check_err("{var:10000}");
+    check_err("{var:}");
+    check_err("{var:3*}");
+    check_err("{var:x}");
+
+    // invalid variable name
+    check_err("{}");
+    check_err("{with space}");
+    check_err("{~thing}");
+    check_err("{a..b}");
+    check_err("{.a.}");
+    check_err("{??hello}");
+    check_err("{!hello}");
+    check_err("{x, y}");
+
+    // unclosed or stray brace
+    check_err("{");
+    check_err("/id*}");
+}
+
+#[test]
+fn strict() -> Result<()> {
+    // accepted by both
+    for template in [
+        "http://a/{b}",
+        "http://%E3%81%82/{+b}",
+        "http://あ/{?x,y}",
+        "/{a.b}{/Some%20Thing*}",
+        "(!$&'*+,;=:@[]~)",
+    ] {
+        UriTemplate::new(template)?;
+        UriTemplate::new_strict(template)?;
+    }
+
+    // invalid literals are percent-encoded by `new`
+    for template in [
+        "a b", "<a>", "\"a\"", "a^b", "a|b", "a\\b", "a`b", "%", "%2", "%2G", "a\tb",
+    ] {
+        UriTemplate::new(template)?;
+        check_strict_err(template);
+    }
+
+    // rejected by both
+    for template in ["{a", "a}", "{a{b}}", "{{a}}", "{}", "{a b}", "{a,}"] {
+        check_err(template);
+        check_strict_err(template);
+    }
+    Ok(())
+}
+
+#[test]
+fn error_kind() {
+    check_err_kind("/a/{b", ErrorKind::UnclosedExpression, 3..5);
+    check_err_kind("/a/{}", ErrorKind::EmptyExpression, 3..5);
+    check_err_kind("/a/b}", ErrorKind::UnexpectedCloseBrace, 4..5);
+    check_err_kind("/a/{b{c}}", ErrorKind::NestedBrace, 5..6);
+    check_err_kind("/a/{!b}", ErrorKind::UnknownOperator, 4..5);
+    check_err_kind("/a/{?b,c d}", ErrorKind::InvalidVarName, 7..10);
+    check_err_kind("/a/{b:0}", ErrorKind::InvalidModifier, 5..7);
+
+    let e = UriTemplate::new_strict("/a b").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidLiteral);
+    assert_eq!(e.span(), 2..3);
+
+    let e = UriTemplate::new_strict("/a%2G").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidPercentEncoding);
+    assert_eq!(e.span(), 2..3);
+
+    let e = UriTemplate::new("{keys:1}")
+        .unwrap()
+        .try_expand(&BTreeMap::from([("keys", vec!["a"])]))
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::PrefixOnCompositeValue);
+    assert_eq!(e.span(), 5..7);
+
+    let template = UriTemplate::new("/{a}").unwrap();
+    let captures = template.captures("/x%F8%28").unwrap();
+    let e = captures.name("a").unwrap().value().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidUtf8);
+    assert_eq!(e.text(), "x%F8%28");
+    assert_eq!(e.span(), 1..4);
 }
 
 #[test]
@@ -116,6 +201,170 @@ fn fragment_expansion() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn multiple_variables() -> Result<()> {
+    check_both("map?{x,y}", "map?1024,768", &[("x", "1024"), ("y", "768")])?;
+    check_both(
+        "{x,hello,y}",
+        "1024,Hello%20World%21,768",
+        &[("x", "1024"), ("hello", "Hello World!"), ("y", "768")],
+    )?;
+    check_both("?{x,empty}", "?1024,", &[("x", "1024"), ("empty", "")])?;
+    check_both("?{x,undef}", "?1024", &[("x", "1024")])?;
+    check_expand(
+        "{+x,hello,y}",
+        "1024,Hello%20World!,768",
+        &[("x", "1024"), ("hello", "Hello World!"), ("y", "768")],
+    )?;
+    check_both(
+        "{+path,x}/here",
+        "/foo/bar,1024/here",
+        &[("path", "/foo/bar"), ("x", "1024")],
+    )?;
+    check_both(
+        "{#path,x}/here",
+        "#/foo/bar,1024/here",
+        &[("path", "/foo/bar"), ("x", "1024")],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn label_expansion() -> Result<()> {
+    check_both("X{.var}", "X.value", &[("var", "value")])?;
+    check_both("X{.empty}", "X.", &[("empty", "")])?;
+    check_both("X{.undef}", "X", &[])?;
+    check_both(
+        "{.half,who}",
+        ".50%25.fred",
+        &[("half", "50%"), ("who", "fred")],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn path_segment_expansion() -> Result<()> {
+    check_both("{/who}", "/fred", &[("who", "fred")])?;
+    check_both(
+        "{/who,dub}",
+        "/fred/me%2Ftoo",
+        &[("who", "fred"), ("dub", "me/too")],
+    )?;
+    check_both(
+        "{/var,empty}",
+        "/value/",
+        &[("var", "value"), ("empty", "")],
+    )?;
+    check_both(
+        "{/var,x}/here",
+        "/value/1024/here",
+        &[("var", "value"), ("x", "1024")],
+    )?;
+    check_not_match("{/var}/here", "/value/1024/here")?;
+    Ok(())
+}
+
+#[test]
+fn path_parameter_expansion() -> Result<()> {
+    check_both("{;who}", ";who=fred", &[("who", "fred")])?;
+    check_both("{;empty}", ";empty", &[("empty", "")])?;
+    check_both(
+        "{;v,empty,who}",
+        ";v=6;empty;who=fred",
+        &[("v", "6"), ("empty", ""), ("who", "fred")],
+    )?;
+    check_both("{;x,y}", ";y=768", &[("y", "768")])?;
+    check_not_match("{;x}", ";y=768")?;
+    Ok(())
+}
+
+#[test]
+fn query_expansion() -> Result<()> {
+    check_both("{?who}", "?who=fred", &[("who", "fred")])?;
+    check_both("{?half}", "?half=50%25", &[("half", "50%")])?;
+    check_both(
+        "{?x,y,empty}",
+        "?x=1024&y=768&empty=",
+        &[("x", "1024"), ("y", "768"), ("empty", "")],
+    )?;
+    check_both("{?x,y}", "?y=768", &[("y", "768")])?;
+    check_not_match("{?x,y}", "?y=768&x=1024")?;
+    Ok(())
+}
+
+#[test]
+fn query_continuation_expansion() -> Result<()> {
+    check_both("{&who}", "&who=fred", &[("who", "fred")])?;
+    check_both("?fixed=yes{&x}", "?fixed=yes&x=1024", &[("x", "1024")])?;
+    check_both(
+        "{&x,y,empty}",
+        "&x=1024&y=768&empty=",
+        &[("x", "1024"), ("y", "768"), ("empty", "")],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn prefix_modifier() -> Result<()> {
+    check_expand("{var:3}", "val", &[("var", "value")])?;
+    check_expand("{var:30}", "value", &[("var", "value")])?;
+    check_both("{var:30}", "value", &[("var", "value")])?;
+    check_expand("{+path:6}/here", "/foo/b/here", &[("path", "/foo/bar")])?;
+    check_expand("{#path:6}/here", "#/foo/b/here", &[("path", "/foo/bar")])?;
+    check_expand("X{.var:3}", "X.val", &[("var", "value")])?;
+    check_expand("{/var:1,var}", "/v/value", &[("var", "value")])?;
+    check_expand("{;hello:5}", ";hello=Hello", &[("hello", "Hello World!")])?;
+    check_expand("{?var:3}", "?var=val", &[("var", "value")])?;
+    check_expand("{&var:3}", "&var=val", &[("var", "value")])?;
+    check_expand("{var:2}", "%E3%81%82%E3%81%84", &[("var", "あいう")])?; // counts characters
+    Ok(())
+}
+
+#[test]
+fn prefix_modifier_captures() -> Result<()> {
+    check_both("{var:3}", "val", &[("var", "val")])?;
+    check_both("X{.var:3}", "X.val", &[("var", "val")])?;
+    check_both("{?var:3}", "?var=val", &[("var", "val")])?;
+    check_both("{var:2}", "%E3%81%82%E3%81%84", &[("var", "あい")])?;
+    check_both("{var:2}", "a%20", &[("var", "a ")])?;
+    check_not_match("{var:3}", "value")?;
+    check_not_match("{?var:3}", "?var=value")?;
+    check_not_match("{var:2}", "%E3%81%82%E3%81%84%E3%81%86")?;
+    check_both("{x:3}{y}", "abcdef", &[("x", "abc"), ("y", "def")])?;
+
+    let vars: Vec<_> = (0..12).map(|i| format!("v{i}:100")).collect();
+    let template = UriTemplate::new(&format!("{{?{}}}", vars.join(",")))?;
+    assert!(template.captures("?v0=a&v11=b").is_some());
+    Ok(())
+}
+
+#[test]
+fn many_vars() -> Result<()> {
+    let vars: Vec<_> = (0..200).map(|i| format!("v{i}")).collect();
+    let template = UriTemplate::new(&format!("{{?{}}}", vars.join(",")))?;
+    let captures = template.captures("?v1=a&v199=b").unwrap();
+    assert!(captures.name("v0").is_none());
+    assert_eq!(captures.name("v1").unwrap().value()?, "a");
+    assert_eq!(captures.name("v199").unwrap().value()?, "b");
+
+    let vars: Vec<_> = (0..10000).map(|i| format!("v{i}")).collect();
+    check_err_kind(
+        &format!("{{{}}}", vars.join(",")),
+        ErrorKind::TooComplex,
+        0..58891,
+    );
+    Ok(())
+}
+
+#[test]
+fn explode_modifier() -> Result<()> {
+    check_both("{var*}", "value", &[("var", "value")])?;
+    check_both("{/id*}", "/person", &[("id", "person")])?;
+    check_both("{;who*}", ";who=fred", &[("who", "fred")])?;
+    check_both("{?who*}", "?who=fred", &[("who", "fred")])?;
+    Ok(())
+}
+
 #[track_caller]
 fn check_both(template: &str, e: &str, vars: &[(&str, &str)]) -> Result<()> {
     let template = UriTemplate::new(template)?;
@@ -173,3 +422,92 @@ fn check_err(template: &str) {
     let ret = UriTemplate::new(template);
     assert!(ret.is_err(), "expect error, template = `{template}`");
 }
+
+#[track_caller]
+fn check_strict_err(template: &str) {
+    let ret = UriTemplate::new_strict(template);
+    assert!(ret.is_err(), "expect error, template = `{template}`");
+}
+
+#[track_caller]
+fn check_err_kind(template: &str, kind: ErrorKind, span: std::ops::Range<usize>) {
+    let e = UriTemplate::new(template).unwrap_err();
+    assert_eq!(e.kind(), kind, "template = `{template}`");
+    assert_eq!(e.span(), span, "template = `{template}`");
+    assert_eq!(e.source_index(), span.start, "template = `{template}`");
+    assert_eq!(e.text(), template);
+}
+
+#[test]
+fn specificity() {
+    use std::cmp::Ordering;
+    let cmp = |a: &str, b: &str| {
+        UriTemplate::new(a)
+            .unwrap()
+            .cmp_specificity(&UriTemplate::new(b).unwrap())
+    };
+    assert_eq!(cmp("/users/me", "/users/{id}"), Ordering::Greater);
+    assert_eq!(cmp("/files/{name}", "/files/{+path}"), Ordering::Greater);
+    assert_eq!(cmp("/files{/path}", "/files{/path*}"), Ordering::Greater);
+    assert_eq!(cmp("/{id}{.format}", "/{id}"), Ordering::Greater);
+    assert_eq!(cmp("/{a}/x", "/x/{b}"), Ordering::Equal);
+    assert_eq!(cmp("/a/{+b}", "/{x}"), Ordering::Greater);
+}
+
+#[test]
+fn overlaps() {
+    let overlaps = |a: &str, b: &str| {
+        let a = UriTemplate::new(a).unwrap();
+        let b = UriTemplate::new(b).unwrap();
+        let witness = a.overlaps(&b);
+        if let Some(witness) = &witness {
+            assert!(
+                a.captures(witness).is_some(),
+                "{a} does not match {witness}"
+            );
+            assert!(
+                b.captures(witness).is_some(),
+                "{b} does not match {witness}"
+            );
+        }
+        assert_eq!(b.overlaps(&a).is_some(), witness.is_some());
+        witness
+    };
+    assert_eq!(overlaps("/a/{x}/b", "/a/c/{y}").as_deref(), Some("/a/c/b"));
+    assert_eq!(
+        overlaps("/users/me", "/users/{id}").as_deref(),
+        Some("/users/me")
+    );
+    assert!(overlaps("/files/{name}", "/files/{+path}").is_some());
+    assert!(overlaps("/files/{name}", "/files/{a}/{b}").is_none());
+    assert!(overlaps("/users/{id}", "/groups/{id}").is_none());
+    assert!(overlaps("/a{/x*}", "/a/b/c/d").is_some());
+    assert!(overlaps("/a{?x,y}", "/a?y=1").is_some());
+    assert!(overlaps("/a{?x}", "/a?y=1").is_none());
+    assert!(overlaps("/{p}/{q}", "/{x}/{x}").is_some());
+    assert!(overlaps("/{p}/b", "/{x}/{x}").is_some());
+    assert!(overlaps("/a/{p}", "/{x}/{x}").is_some());
+    assert!(overlaps("/a/b", "/{x}/{x}").is_none());
+}
+
+#[test]
+fn is_subset_of() {
+    let is_subset_of = |a: &str, b: &str| {
+        UriTemplate::new(a)
+            .unwrap()
+            .is_subset_of(&UriTemplate::new(b).unwrap())
+    };
+    assert!(is_subset_of("/v1/users/{id}", "/v1/{+rest}"));
+    assert!(!is_subset_of("/v1/{+rest}", "/v1/users/{id}"));
+    assert!(is_subset_of("/users/me", "/users/{id}"));
+    assert!(!is_subset_of("/users/{id}", "/users/me"));
+    assert!(is_subset_of("/files/{name}", "/files/{+path}"));
+    assert!(!is_subset_of("/files/{+path}", "/files/{name}"));
+    assert!(is_subset_of("/a/{x}", "/a/{y}"));
+    assert!(is_subset_of("/a{/x}", "/a{+x}"));
+    assert!(!is_subset_of("/a/{x}", "/b/{x}"));
+    assert!(is_subset_of("/a{?x}", "/a{?x,y}"));
+    assert!(!is_subset_of("/{p}/{q}", "/{x}/{x}"));
+    assert!(is_subset_of("/{x}/{x}", "/{x}/{x}"));
+    assert!(is_subset_of("/{x}/{x}", "/{p}/{q}"));
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+
+use uri_template_ex::{UriTemplate, Value};
+
+fn rfc_vars() -> BTreeMap<&'static str, Value<'static>> {
+    let mut vars = BTreeMap::new();
+    vars.insert("var", Value::String("value".into()));
+    vars.insert("path", Value::String("/foo/bar".into()));
+    vars.insert(
+        "list",
+        Value::List(vec!["red".into(), "green".into(), "blue".into()]),
+    );
+    vars.insert(
+        "keys",
+        Value::AssocArray(vec![
+            ("semi".into(), ";".into()),
+            ("dot".into(), ".".into()),
+            ("comma".into(), ",".into()),
+        ]),
+    );
+    vars.insert("empty_list", Value::List(Vec::new()));
+    vars.insert("empty_keys", Value::AssocArray(Vec::new()));
+    vars
+}
+
+#[track_caller]
+fn check(template: &str, expected: &str) {
+    let template = UriTemplate::new(template).unwrap();
+    assert_eq!(
+        template.expand(&rfc_vars()),
+        expected,
+        "template = `{template}`"
+    );
+}
+
+#[test]
+fn list() {
+    check("{list}", "red,green,blue");
+    check("{list*}", "red,green,blue");
+    check("{+list}", "red,green,blue");
+    check("{#list}", "#red,green,blue");
+    check("{#list*}", "#red,green,blue");
+    check("X{.list}", "X.red,green,blue");
+    check("X{.list*}", "X.red.green.blue");
+    check("{/list}", "/red,green,blue");
+    check("{/list*}", "/red/green/blue");
+    check("{/list*,path:4}", "/red/green/blue/%2Ffoo");
+    check("{;list}", ";list=red,green,blue");
+    check("{;list*}", ";list=red;list=green;list=blue");
+    check("{?list}", "?list=red,green,blue");
+    check("{?list*}", "?list=red&list=green&list=blue");
+    check("{&list}", "&list=red,green,blue");
+    check("{&list*}", "&list=red&list=green&list=blue");
+}
+
+#[test]
+fn assoc_array() {
+    check("{keys}", "semi,%3B,dot,.,comma,%2C");
+    check("{keys*}", "semi=%3B,dot=.,comma=%2C");
+    check("{+keys}", "semi,;,dot,.,comma,,");
+    check("{+keys*}", "semi=;,dot=.,comma=,");
+    check("{#keys}", "#semi,;,dot,.,comma,,");
+    check("{#keys*}", "#semi=;,dot=.,comma=,");
+    check("X{.keys}", "X.semi,%3B,dot,.,comma,%2C");
+    check("X{.keys*}", "X.semi=%3B.dot=..comma=%2C");
+    check("{/keys}", "/semi,%3B,dot,.,comma,%2C");
+    check("{/keys*}", "/semi=%3B/dot=./comma=%2C");
+    check("{;keys}", ";keys=semi,%3B,dot,.,comma,%2C");
+    check("{;keys*}", ";semi=%3B;dot=.;comma=%2C");
+    check("{?keys}", "?keys=semi,%3B,dot,.,comma,%2C");
+    check("{?keys*}", "?semi=%3B&dot=.&comma=%2C");
+    check("{&keys}", "&keys=semi,%3B,dot,.,comma,%2C");
+    check("{&keys*}", "&semi=%3B&dot=.&comma=%2C");
+}
+
+#[test]
+fn empty_composite_is_undefined() {
+    check("X{.empty_list}", "X");
+    check("X{.empty_keys*}", "X");
+    check("{?var,empty_list}", "?var=value");
+    check("{?empty_keys*,var}", "?var=value");
+}
+
+#[test]
+fn prefix_on_composite() {
+    let template = UriTemplate::new("{keys:1}").unwrap();
+    assert!(template.try_expand(&rfc_vars()).is_err());
+    let template = UriTemplate::new("{+list:1}").unwrap();
+    assert!(template.try_expand(&rfc_vars()).is_err());
+    let template = UriTemplate::new("{var:3}").unwrap();
+    assert_eq!(template.try_expand(&rfc_vars()).unwrap(), "val");
+}
+
+#[test]
+fn collection_values() {
+    let template = UriTemplate::new("{/path*}{?q*}").unwrap();
+
+    let mut vars = HashMap::new();
+    vars.insert("path", vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(template.expand(&vars), "/a/b");
+
+    let path: &[&str] = &["x", "y z"];
+    let mut vars = BTreeMap::new();
+    vars.insert("path", path);
+    assert_eq!(template.expand(&vars), "/x/y%20z");
+
+    let mut q = BTreeMap::new();
+    q.insert("a".to_string(), "1".to_string());
+    q.insert("b".to_string(), "2".to_string());
+    let mut vars = BTreeMap::new();
+    vars.insert("q", q);
+    assert_eq!(template.expand(&vars), "?a=1&b=2");
+}
+
+#[test]
+fn positional_values() {
+    let template = UriTemplate::new("{a}{/b*}").unwrap();
+    let vars = [
+        Value::String("x".into()),
+        Value::List(vec!["1".into(), "2".into()]),
+    ];
+    assert_eq!(template.expand(&vars[..]), "x/1/2");
+}
+
+#[test]
+fn expand_from_serialize() -> Result<(), Box<dyn std::error::Error>> {
+    #[derive(serde::Serialize)]
+    struct Params {
+        user: &'static str,
+        id: u64,
+        draft: bool,
+        lang: Option<String>,
+        path: Vec<&'static str>,
+        keys: BTreeMap<&'static str, i32>,
+    }
+    let params = Params {
+        user: "alice smith",
+        id: 42,
+        draft: false,
+        lang: None,
+        path: vec!["a", "b"],
+        keys: [("x", 1), ("y", 2)].into(),
+    };
+    let vars = uri_template_ex::vars::from_serialize(&params)?;
+    let template = UriTemplate::new("/users/{user}/{id}{/path*}{?draft,lang,keys*}")?;
+    assert_eq!(
+        template.expand(&vars),
+        "/users/alice%20smith/42/a/b?draft=false&x=1&y=2"
+    );
+    assert!(uri_template_ex::vars::from_serialize(&[1, 2]).is_err());
+    Ok(())
+}
+
+#[test]
+fn expand_json() {
+    let json = serde_json::json!({
+        "id": 42,
+        "draft": true,
+        "lang": null,
+        "path": ["a", 1],
+        "keys": { "x": "1", "y": null },
+        "name": "a b",
+    });
+    let template = UriTemplate::new("/{id}{/path*}{?draft,lang,keys*}{#name}").unwrap();
+    let expected = "/42/a/1?draft=true&x=1#a%20b";
+    assert_eq!(template.expand(&json), expected);
+    assert_eq!(template.expand(json.as_object().unwrap()), expected);
+    assert_eq!(template.expand(&serde_json::json!([1, 2])), "/");
+}
@@ -0,0 +1,208 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use structmeta::{Flag, NameValue, StructMeta};
+use syn::{
+    Data, DeriveInput, Expr, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Result,
+    Type, ext::IdentExt, parse_quote,
+};
+
+macro_rules! bail {
+    ($span:expr, $($arg:tt)*) => {
+        return Err(syn::Error::new_spanned(&$span, format!($($arg)*)))
+    };
+}
+
+#[derive(StructMeta, Default)]
+struct FieldArgs {
+    rename: Option<LitStr>,
+    skip: Flag,
+    display: Flag,
+    default: Option<NameValue<Option<Expr>>>,
+}
+
+struct FieldEntry<'a> {
+    field: &'a Field,
+    ident: &'a Ident,
+    name: String,
+    args: FieldArgs,
+}
+
+fn fields<'a>(input: &'a DeriveInput, derive: &str) -> Result<Vec<FieldEntry<'a>>> {
+    let Data::Struct(data) = &input.data else {
+        bail!(
+            input.ident,
+            "`#[derive({derive})]` supports only structs with named fields"
+        );
+    };
+    let Fields::Named(fields) = &data.fields else {
+        bail!(
+            input.ident,
+            "`#[derive({derive})]` supports only structs with named fields"
+        );
+    };
+    let mut entries = Vec::new();
+    for field in &fields.named {
+        let mut args = FieldArgs::default();
+        for attr in &field.attrs {
+            if attr.path().is_ident("uri_template") {
+                let a: FieldArgs = attr.parse_args()?;
+                args.rename = a.rename.or(args.rename);
+                args.skip.span = a.skip.span.or(args.skip.span);
+                args.display.span = a.display.span.or(args.display.span);
+                args.default = a.default.or(args.default);
+            }
+        }
+        let ident = field.ident.as_ref().unwrap();
+        let name = match &args.rename {
+            Some(rename) => rename.value(),
+            None => ident.unraw().to_string(),
+        };
+        entries.push(FieldEntry {
+            field,
+            ident,
+            name,
+            args,
+        });
+    }
+    Ok(entries)
+}
+
+pub fn build_vars(input: &DeriveInput) -> Result<TokenStream> {
+    let mut arms = Vec::new();
+    let mut names = Vec::new();
+    for entry in fields(input, "Vars")? {
+        if entry.args.skip.value() {
+            continue;
+        }
+        let ident = entry.ident;
+        let name = &entry.name;
+        names.push(name.clone());
+        let to_value = if entry.args.display.value() {
+            quote!(::uri_template_ex::__private::display_value(value))
+        } else {
+            quote!(::uri_template_ex::ToValue::to_value(value))
+        };
+        let value = if generic_arg(&entry.field.ty, "Option").is_some() {
+            quote!(::uri_template_ex::__private::Option::map(
+                ::uri_template_ex::__private::Option::as_ref(&self.#ident),
+                |value| #to_value
+            ))
+        } else {
+            quote!({
+                let value = &self.#ident;
+                ::uri_template_ex::__private::Some(#to_value)
+            })
+        };
+        arms.push(quote!(#name => #value,));
+    }
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('__a));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (impl_generics_self, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics_self ::uri_template_ex::__private::VarNames for #ident #type_generics #where_clause {
+            const VAR_NAMES: &'static [&'static str] = &[#(#names),*];
+        }
+        #[automatically_derived]
+        impl #impl_generics ::uri_template_ex::Vars for &'__a #ident #type_generics #where_clause {
+            fn var(
+                &mut self,
+                index: usize,
+                name: &str,
+            ) -> ::uri_template_ex::__private::Option<::uri_template_ex::__private::Cow<'_, str>> {
+                ::uri_template_ex::__private::into_string(
+                    ::uri_template_ex::Vars::value(self, index, name)?
+                )
+            }
+            fn value(
+                &mut self,
+                _index: usize,
+                name: &str,
+            ) -> ::uri_template_ex::__private::Option<::uri_template_ex::Value<'_>> {
+                match name {
+                    #(#arms)*
+                    _ => ::uri_template_ex::__private::None,
+                }
+            }
+        }
+    })
+}
+
+pub fn build_from_captures(input: &DeriveInput) -> Result<TokenStream> {
+    let mut inits = Vec::new();
+    for entry in fields(input, "FromCaptures")? {
+        let ident = entry.ident;
+        let name = &entry.name;
+        let default = match &entry.args.default {
+            Some(NameValue {
+                value: Some(expr), ..
+            }) => Some(quote!(#expr)),
+            Some(NameValue { value: None, .. }) => {
+                Some(quote!(::uri_template_ex::__private::Default::default()))
+            }
+            None => None,
+        };
+        let value = if entry.args.skip.value() {
+            default.unwrap_or(quote!(::uri_template_ex::__private::Default::default()))
+        } else if generic_arg(&entry.field.ty, "Vec").is_some() {
+            quote!(::uri_template_ex::__private::list_field(captures, #name)?)
+        } else if generic_arg(&entry.field.ty, "Option").is_some() {
+            let value = quote!(::uri_template_ex::__private::optional_field(captures, #name)?);
+            match default {
+                Some(default) => quote!(match #value {
+                    ::uri_template_ex::__private::None => #default,
+                    value => value,
+                }),
+                None => value,
+            }
+        } else {
+            match default {
+                Some(default) => quote!(
+                    match ::uri_template_ex::__private::optional_field(captures, #name)? {
+                        ::uri_template_ex::__private::Some(value) => value,
+                        ::uri_template_ex::__private::None => #default,
+                    }
+                ),
+                None => quote!(::uri_template_ex::__private::field(captures, #name)?),
+            }
+        };
+        inits.push(quote!(#ident: #value,));
+    }
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::uri_template_ex::FromCaptures for #ident #type_generics #where_clause {
+            fn from_captures(
+                captures: &::uri_template_ex::Captures,
+            ) -> ::uri_template_ex::__private::Result<Self, ::uri_template_ex::DeserializeError> {
+                ::uri_template_ex::__private::Ok(Self {
+                    #(#inits)*
+                })
+            }
+        }
+    })
+}
+
+/// Returns `T` if `ty` is `name<T>`.
+fn generic_arg<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    if ty.qself.is_some() {
+        return None;
+    }
+    let segment = ty.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
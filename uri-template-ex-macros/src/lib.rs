@@ -0,0 +1,77 @@
+//! Procedural macros for [`uri-template-ex`](https://docs.rs/uri-template-ex/).
+//!
+//! Use these macros through the `derive` and `macros` features of `uri-template-ex`.
+
+mod derive;
+mod template;
+mod uri_template;
+
+use syn::{DeriveInput, parse_macro_input};
+
+/// Implements `Vars` for a reference to a struct with named fields.
+///
+/// Each field is a variable with the same name.
+/// Fields are converted with `ToValue`, and fields of type `Option<T>` are undefined when `None`.
+///
+/// Field attributes:
+///
+/// - `#[uri_template(rename = "name")]` : use `name` as the variable name
+/// - `#[uri_template(skip)]` : do not use the field as a variable
+/// - `#[uri_template(display)]` : convert the field with `Display` instead of `ToValue`
+///
+/// `#[uri_template(default)]`, which is used by `#[derive(FromCaptures)]`, is ignored.
+#[proc_macro_derive(Vars, attributes(uri_template))]
+pub fn derive_vars(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_macro_output(derive::build_vars(&input))
+}
+
+/// Implements `FromCaptures` for a struct with named fields.
+///
+/// Each field is parsed from the captured variable with the same name using `FromStr`.
+/// Fields of type `Option<T>` are `None` when the variable is not captured,
+/// and fields of type `Vec<T>` are parsed from the items of a list.
+///
+/// Field attributes:
+///
+/// - `#[uri_template(rename = "name")]` : use `name` as the variable name
+/// - `#[uri_template(skip)]` : do not capture the field and use `Default::default()`
+/// - `#[uri_template(default)]` : use `Default::default()` when the variable is not captured
+/// - `#[uri_template(default = expr)]` : use `expr` when the variable is not captured
+///
+/// `#[uri_template(display)]`, which is used by `#[derive(Vars)]`, is ignored,
+/// since the field is parsed with `FromStr` anyway.
+#[proc_macro_derive(FromCaptures, attributes(uri_template))]
+pub fn derive_from_captures(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_macro_output(derive::build_from_captures(&input))
+}
+
+/// Creates a `&'static UriTemplate` from a template checked at compile time.
+///
+/// The template is parsed on first use.
+///
+/// `uri_template!("/users/{id}", Params)` also checks that `Params`,
+/// a type that implements `Vars` by `#[derive(Vars)]`, provides all variables of the template.
+///
+/// `uri_template!(UserFile = "/users/{user}/files/{+path}")` defines a struct
+/// with one field for each variable, and `template()`, `expand()` and `parse()` methods.
+/// Fields of variables without an operator or with `+` are `String`, which is empty when not captured.
+/// Fields of variables with the other operators are `Option<String>`, which is `None` when not captured
+/// and omits the variable from `expand()`.
+/// Fields of exploded variables are `Vec<String>`,
+/// or `Vec<(String, String)>` with `;`, `?` and `&`, which have the keys of an associative array.
+/// The visibility and attributes written before the name are applied to the struct.
+#[proc_macro]
+pub fn uri_template(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as uri_template::Input);
+    into_macro_output(uri_template::build(input))
+}
+
+fn into_macro_output(input: syn::Result<proc_macro2::TokenStream>) -> proc_macro::TokenStream {
+    match input {
+        Ok(s) => s,
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
@@ -0,0 +1,179 @@
+//! Validation of templates at compile time.
+//!
+//! This follows the grammar accepted by `UriTemplate::new`.
+
+use std::ops::Range;
+
+pub struct Template {
+    pub vars: Vec<Var>,
+}
+
+pub struct Var {
+    pub name: String,
+    pub explode: bool,
+    pub op: Option<char>,
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut vars = Vec::new();
+        let mut iter = s.char_indices().peekable();
+        while let Some((index, ch)) = iter.next() {
+            match ch {
+                '{' => {
+                    let Some(len) = s[index..].find('}') else {
+                        return Err(error(s, index..s.len(), "expression is not closed"));
+                    };
+                    let end = index + len;
+                    let body = &s[index + 1..end];
+                    if let Some(i) = body.find('{') {
+                        let i = index + 1 + i;
+                        return Err(error(s, i..i + 1, "`{` inside an expression"));
+                    }
+                    parse_expr(s, index + 1..end, &mut vars)?;
+                    while iter.next_if(|&(i, _)| i <= end).is_some() {}
+                }
+                '}' => return Err(error(s, index..index + 1, "`}` without a matching `{`")),
+                _ => {}
+            }
+        }
+        Ok(Self { vars })
+    }
+    pub fn var_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for var in &self.vars {
+            if !names.contains(&var.name.as_str()) {
+                names.push(&var.name);
+            }
+        }
+        names
+    }
+}
+
+fn parse_expr(s: &str, range: Range<usize>, vars: &mut Vec<Var>) -> Result<(), String> {
+    let mut start = range.start;
+    let mut op = None;
+    match s[range.clone()].chars().next() {
+        Some(c @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => {
+            op = Some(c);
+            start += 1;
+        }
+        Some('=' | ',' | '!' | '@' | '|') => {
+            return Err(error(
+                s,
+                start..start + 1,
+                "operator is reserved for future extensions",
+            ));
+        }
+        _ => {}
+    }
+    if start == range.end {
+        return Err(error(
+            s,
+            range.start - 1..range.end + 1,
+            "expression has no variables",
+        ));
+    }
+    for item in s[start..range.end].split(',') {
+        let item_range = start..start + item.len();
+        start = item_range.end + 1;
+        let explode = item.ends_with('*');
+        let name = if let Some(i) = item.find(':') {
+            if !is_max_length(&item[i + 1..]) {
+                return Err(error(
+                    s,
+                    item_range.start + i..item_range.end,
+                    "invalid modifier",
+                ));
+            }
+            &item[..i]
+        } else if let Some(name) = item.strip_suffix('*') {
+            name
+        } else {
+            item
+        };
+        if !is_var_name(name) {
+            let name_range = item_range.start..item_range.start + name.len();
+            return Err(error(s, name_range, "invalid variable name"));
+        }
+        vars.push(Var {
+            name: name.to_string(),
+            explode,
+            op,
+        });
+    }
+    Ok(())
+}
+
+fn error(s: &str, span: Range<usize>, message: &str) -> String {
+    format!("{message}: `{}`", &s[span])
+}
+
+/// `max-length = %x31-39 0*3DIGIT`
+fn is_max_length(s: &str) -> bool {
+    (1..=4).contains(&s.len()) && !s.starts_with('0') && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `varname = varchar *( ["."] varchar )`
+fn is_var_name(s: &str) -> bool {
+    let b = s.as_bytes();
+    let mut after_dot = true;
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'.' if !after_dot => after_dot = true,
+            b'%' if b.len() > i + 2
+                && b[i + 1].is_ascii_hexdigit()
+                && b[i + 2].is_ascii_hexdigit() =>
+            {
+                after_dot = false;
+                i += 2;
+            }
+            c if c.is_ascii_alphanumeric() || c == b'_' => after_dot = false,
+            _ => return false,
+        }
+        i += 1;
+    }
+    !after_dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Template;
+    use uri_template_ex::UriTemplate;
+
+    #[test]
+    fn same_as_uri_template_new() {
+        let templates = [
+            "",
+            "/a/{b}",
+            "{+a}{#b}{.c}{/d}{;e}{?f}{&g}",
+            "{a,b.c,d_e,%41}",
+            "{a:1}{b:9999}{c*}",
+            "%7B{a}%7D",
+            "{",
+            "}",
+            "{}",
+            "{+}",
+            "{a{b}",
+            "{a,}",
+            "{,a}",
+            "{=a}",
+            "{a:0}",
+            "{a:10000}",
+            "{a:1*}",
+            "{a b}",
+            "{a..b}",
+            "{.a.}",
+            "{%4}",
+            "{a}}",
+        ];
+        for s in templates {
+            assert_eq!(
+                Template::parse(s).is_ok(),
+                UriTemplate::new(s).is_ok(),
+                "template = `{s}`"
+            );
+        }
+    }
+}
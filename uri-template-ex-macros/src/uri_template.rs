@@ -0,0 +1,332 @@
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use quote::quote;
+use syn::{
+    Attribute, Ident, LitStr, Result, Token, Type, Visibility,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+};
+
+use crate::template::{Template, Var};
+
+pub enum Input {
+    /// `"template"` or `"template", Vars`
+    Template(TemplateInput),
+    /// `Name = "template"`
+    Struct(StructInput),
+}
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(LitStr) {
+            let template = input.parse()?;
+            let mut vars = None;
+            if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+                vars = Some(input.parse()?);
+                input.parse::<Option<Token![,]>>()?;
+            }
+            Ok(Self::Template(TemplateInput { template, vars }))
+        } else {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let vis = input.parse()?;
+            let ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let template = input.parse()?;
+            input.parse::<Option<Token![;]>>()?;
+            Ok(Self::Struct(StructInput {
+                attrs,
+                vis,
+                ident,
+                template,
+            }))
+        }
+    }
+}
+
+pub struct TemplateInput {
+    template: LitStr,
+    vars: Option<Type>,
+}
+
+pub struct StructInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    template: LitStr,
+}
+
+pub fn build(input: Input) -> Result<TokenStream> {
+    match input {
+        Input::Template(input) => build_template(input),
+        Input::Struct(input) => build_struct(input),
+    }
+}
+
+fn parse_template(template: &LitStr) -> Result<Template> {
+    Template::parse(&template.value()).map_err(|message| syn::Error::new(template.span(), message))
+}
+
+fn build_template(input: TemplateInput) -> Result<TokenStream> {
+    let template = parse_template(&input.template)?;
+    let mut checks = Vec::new();
+    if let Some(ty) = &input.vars {
+        for name in template.var_names() {
+            let message = format!(
+                "`{}` does not provide variable `{name}`",
+                quote!(#ty).to_string().replace(' ', "")
+            );
+            checks.push(quote! {
+                const _: () = ::std::assert!(
+                    ::uri_template_ex::__private::contains(
+                        <#ty as ::uri_template_ex::__private::VarNames>::VAR_NAMES,
+                        #name,
+                    ),
+                    #message
+                );
+            });
+        }
+    }
+    let template = static_template(&input.template);
+    Ok(quote! {
+        {
+            #(#checks)*
+            #template
+        }
+    })
+}
+
+fn static_template(template: &LitStr) -> TokenStream {
+    quote! {
+        {
+            static TEMPLATE: ::uri_template_ex::__private::LazyLock<::uri_template_ex::UriTemplate> =
+                ::uri_template_ex::__private::LazyLock::new(|| {
+                    ::uri_template_ex::UriTemplate::new(#template).unwrap()
+                });
+            let template: &'static ::uri_template_ex::UriTemplate = &TEMPLATE;
+            template
+        }
+    }
+}
+
+fn build_struct(input: StructInput) -> Result<TokenStream> {
+    let template = parse_template(&input.template)?;
+    let mut vars: Vec<(&str, FieldKind)> = Vec::new();
+    for var in &template.vars {
+        let kind = FieldKind::new(var);
+        match vars.iter_mut().find(|(name, _)| *name == var.name) {
+            Some((_, k)) => *k = k.merge(kind),
+            None => vars.push((&var.name, kind)),
+        }
+    }
+    let StructInput {
+        attrs, vis, ident, ..
+    } = &input;
+    let mut fields = Vec::new();
+    let mut values = Vec::new();
+    let mut parses = Vec::new();
+    let mut names: Vec<(String, &str)> = Vec::new();
+    for (name, kind) in vars {
+        let field = field_ident(name);
+        let field_name = field.to_string();
+        if let Some((_, other)) = names.iter().find(|(f, _)| *f == field_name) {
+            return Err(syn::Error::new(
+                input.template.span(),
+                format!(
+                    "variables `{other}` and `{name}` have the same field name `{}`",
+                    field.unraw()
+                ),
+            ));
+        }
+        names.push((field_name, name));
+        match kind {
+            FieldKind::String => {
+                fields.push(quote!(#vis #field: ::std::string::String));
+                values.push(quote! {
+                    #name => ::uri_template_ex::__private::Some(::uri_template_ex::Value::String(
+                        ::uri_template_ex::__private::Cow::Borrowed(&self.#field)
+                    ))
+                });
+                parses.push(quote! {
+                    #field: match captures.name(#name) {
+                        ::uri_template_ex::__private::Some(m) => m.value().ok()?.into_owned(),
+                        ::uri_template_ex::__private::None => ::std::string::String::new(),
+                    }
+                });
+            }
+            FieldKind::Option => {
+                fields.push(quote!(#vis #field: ::uri_template_ex::__private::Option<::std::string::String>));
+                values.push(quote! {
+                    #name => self.#field.as_deref().map(|s| ::uri_template_ex::Value::String(
+                        ::uri_template_ex::__private::Cow::Borrowed(s)
+                    ))
+                });
+                parses.push(quote! {
+                    #field: match captures.name(#name) {
+                        ::uri_template_ex::__private::Some(m) => {
+                            ::uri_template_ex::__private::Some(m.value().ok()?.into_owned())
+                        }
+                        ::uri_template_ex::__private::None => ::uri_template_ex::__private::None,
+                    }
+                });
+            }
+            FieldKind::List => {
+                fields.push(quote!(#vis #field: ::std::vec::Vec<::std::string::String>));
+                values.push(quote! {
+                    #name => ::uri_template_ex::__private::Some(::uri_template_ex::Value::List(
+                        self.#field.iter().map(|s| ::uri_template_ex::__private::Cow::Borrowed(s.as_str())).collect()
+                    ))
+                });
+                parses.push(quote! {
+                    #field: match captures.name(#name) {
+                        ::uri_template_ex::__private::Some(m) => {
+                            m.values().ok()?.into_iter().map(|s| s.into_owned()).collect()
+                        }
+                        ::uri_template_ex::__private::None => ::std::vec::Vec::new(),
+                    }
+                });
+            }
+            FieldKind::AssocArray => {
+                fields.push(quote! {
+                    #vis #field: ::std::vec::Vec<(::std::string::String, ::std::string::String)>
+                });
+                values.push(quote! {
+                    #name => ::uri_template_ex::__private::Some(::uri_template_ex::Value::AssocArray(
+                        self.#field
+                            .iter()
+                            .map(|(k, v)| {
+                                (
+                                    ::uri_template_ex::__private::Cow::Borrowed(k.as_str()),
+                                    ::uri_template_ex::__private::Cow::Borrowed(v.as_str()),
+                                )
+                            })
+                            .collect()
+                    ))
+                });
+                parses.push(quote! {
+                    #field: match captures.name(#name) {
+                        ::uri_template_ex::__private::Some(m) => m
+                            .pairs()
+                            .ok()?
+                            .into_iter()
+                            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                            .collect(),
+                        ::uri_template_ex::__private::None => ::std::vec::Vec::new(),
+                    }
+                });
+            }
+        }
+    }
+    let template = static_template(&input.template);
+    let doc = format!("Variables of `{}`.", input.template.value());
+    Ok(quote! {
+        #[doc = #doc]
+        #(#attrs)*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        #vis struct #ident {
+            #(#fields,)*
+        }
+        impl #ident {
+            /// Returns the template.
+            #vis fn template() -> &'static ::uri_template_ex::UriTemplate {
+                #template
+            }
+            /// Expands the template with the fields.
+            #vis fn expand(&self) -> ::std::string::String {
+                Self::template().expand(self)
+            }
+            /// Creates a value from the variables captured by the template.
+            #vis fn parse(input: &str) -> ::uri_template_ex::__private::Option<Self> {
+                let captures = Self::template().captures(input)?;
+                ::uri_template_ex::__private::Some(Self {
+                    #(#parses,)*
+                })
+            }
+        }
+        #[automatically_derived]
+        impl ::uri_template_ex::Vars for &#ident {
+            fn var(
+                &mut self,
+                index: usize,
+                name: &str,
+            ) -> ::uri_template_ex::__private::Option<::uri_template_ex::__private::Cow<'_, str>> {
+                ::uri_template_ex::__private::into_string(
+                    ::uri_template_ex::Vars::value(self, index, name)?
+                )
+            }
+            fn value(
+                &mut self,
+                _index: usize,
+                name: &str,
+            ) -> ::uri_template_ex::__private::Option<::uri_template_ex::Value<'_>> {
+                match name {
+                    #(#values,)*
+                    _ => ::uri_template_ex::__private::None,
+                }
+            }
+        }
+    })
+}
+
+/// Type of the field of a variable.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    /// `String` for variables without an operator or with `+`
+    String,
+    /// `Option<String>` for variables with the other operators, which are omitted when undefined
+    Option,
+    /// `Vec<String>` for exploded variables
+    List,
+    /// `Vec<(String, String)>` for exploded variables with `;`, `?` or `&`, which have keys
+    AssocArray,
+}
+impl FieldKind {
+    fn new(var: &Var) -> Self {
+        match (var.explode, var.op) {
+            (true, Some(';' | '?' | '&')) => Self::AssocArray,
+            (true, _) => Self::List,
+            (false, None | Some('+')) => Self::String,
+            (false, _) => Self::Option,
+        }
+    }
+    /// Returns the kind of a variable that appears with both kinds.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::AssocArray, _) | (_, Self::AssocArray) => Self::AssocArray,
+            (Self::List, _) | (_, Self::List) => Self::List,
+            (Self::String, _) | (_, Self::String) => Self::String,
+            (Self::Option, Self::Option) => Self::Option,
+        }
+    }
+}
+
+/// Converts a variable name to a field name. (`a.b` -> `a_b`, `%41` -> `_41`)
+fn field_ident(name: &str) -> Ident {
+    let mut s = String::new();
+    for c in name.chars() {
+        match c {
+            '.' | '%' => s.push('_'),
+            _ => s.push(c),
+        }
+    }
+    if s.starts_with(|c: char| c.is_ascii_digit()) {
+        s.insert(0, '_');
+    }
+    if matches!(s.as_str(), "_" | "crate" | "self" | "Self" | "super") {
+        s.push('_');
+    }
+    syn::parse_str(&s).unwrap_or_else(|_| format_ident!("r#{s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Input, build};
+
+    #[test]
+    fn same_field_name() {
+        let input: Input = syn::parse_str(r#"S = "/{a.b}/{a_b}""#).unwrap();
+        let e = build(input).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "variables `a.b` and `a_b` have the same field name `a_b`"
+        );
+    }
+}
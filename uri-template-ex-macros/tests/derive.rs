@@ -0,0 +1,137 @@
+use std::fmt;
+use std::str::FromStr;
+
+use uri_template_ex::{FromCaptures, UriTemplate, Vars};
+
+#[derive(Debug, PartialEq)]
+struct Lang(String);
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "lang-{}", self.0)
+    }
+}
+impl FromStr for Lang {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("lang-") {
+            Some(s) => Ok(Lang(s.into())),
+            None => Err(format!("unknown language `{s}`")),
+        }
+    }
+}
+
+#[derive(Vars, FromCaptures, Debug, PartialEq)]
+struct Params {
+    user: String,
+    #[uri_template(rename = "file.id")]
+    id: u64,
+    path: Vec<String>,
+    #[uri_template(display)]
+    lang: Lang,
+    q: Option<String>,
+    #[uri_template(default = 10)]
+    per_page: u32,
+    #[uri_template(skip)]
+    cache: Vec<u8>,
+}
+
+fn template() -> UriTemplate {
+    UriTemplate::new("/users/{user}/files/{file.id}{/path*}{?lang,q,per_page}").unwrap()
+}
+
+#[test]
+fn expand() {
+    let params = Params {
+        user: "alice".into(),
+        id: 42,
+        path: vec!["a".into(), "b".into()],
+        lang: Lang("en".into()),
+        q: None,
+        per_page: 20,
+        cache: vec![1],
+    };
+    assert_eq!(
+        template().expand(&params),
+        "/users/alice/files/42/a/b?lang=lang-en&per_page=20"
+    );
+}
+
+#[test]
+fn from_captures() {
+    let template = template();
+    let captures = template
+        .captures("/users/alice/files/42/a/b?lang=lang-en&q=x")
+        .unwrap();
+    assert_eq!(
+        Params::from_captures(&captures).unwrap(),
+        Params {
+            user: "alice".into(),
+            id: 42,
+            path: vec!["a".into(), "b".into()],
+            lang: Lang("en".into()),
+            q: Some("x".into()),
+            per_page: 10,
+            cache: Vec::new(),
+        }
+    );
+}
+
+#[test]
+fn round_trip() {
+    let params = Params {
+        user: "a b".into(),
+        id: 1,
+        path: Vec::new(),
+        lang: Lang("ja".into()),
+        q: Some("c/d".into()),
+        per_page: 5,
+        cache: Vec::new(),
+    };
+    let template = template();
+    let uri = template.expand(&params);
+    let captures = template.captures(&uri).unwrap();
+    assert_eq!(Params::from_captures(&captures).unwrap(), params);
+}
+
+#[test]
+fn from_captures_error() {
+    let template = template();
+    let captures = template
+        .captures("/users/alice/files/x?lang=lang-en")
+        .unwrap();
+    let e = Params::from_captures(&captures).unwrap_err();
+    assert_eq!(e.var(), Some("file.id"));
+
+    let captures = template.captures("/users/alice/files/1?lang=en").unwrap();
+    let e = Params::from_captures(&captures).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "variable `lang`: cannot parse `en`: unknown language `en`"
+    );
+
+    let captures = template.captures("/users/alice/files/1").unwrap();
+    let e = Params::from_captures(&captures).unwrap_err();
+    assert_eq!(e.to_string(), "variable `lang`: missing field `lang`");
+}
+
+#[derive(FromCaptures, Debug, PartialEq)]
+struct DefaultParams {
+    #[uri_template(default)]
+    page: u32,
+    #[uri_template(default = Some(1))]
+    min: Option<u32>,
+}
+
+#[test]
+fn default() {
+    let template = UriTemplate::new("/items{?page,min}").unwrap();
+    let captures = template.captures("/items").unwrap();
+    assert_eq!(
+        DefaultParams::from_captures(&captures).unwrap(),
+        DefaultParams {
+            page: 0,
+            min: Some(1),
+        }
+    );
+}
@@ -0,0 +1,98 @@
+use uri_template_ex::{UriTemplate, Vars, uri_template};
+
+#[derive(Vars)]
+struct Params {
+    user: String,
+    id: u32,
+    #[uri_template(rename = "q")]
+    query: Option<String>,
+}
+
+#[test]
+fn uri_template() {
+    let template: &'static UriTemplate = uri_template!("/users/{user}/files/{id}");
+    assert_eq!(template.to_string(), "/users/{user}/files/{id}");
+    let captures = template.captures("/users/alice/files/1").unwrap();
+    assert_eq!(captures.name("id").unwrap().value().unwrap(), "1");
+}
+
+#[test]
+fn uri_template_same_instance() {
+    let get = || uri_template!("/a/{b}");
+    assert!(std::ptr::eq(get(), get()));
+}
+
+#[test]
+fn uri_template_with_vars() {
+    let params = Params {
+        user: "alice".into(),
+        id: 1,
+        query: Some("x".into()),
+    };
+    let template = uri_template!("/users/{user}/files/{id}{?q}", Params);
+    assert_eq!(template.expand(&params), "/users/alice/files/1?q=x");
+}
+
+uri_template!(UserFile = "/users/{user}/files/{+path}");
+uri_template!(
+    /// Search query.
+    pub(crate) Search = "/search{/scope*}{?q,type,page.size}"
+);
+
+#[test]
+fn uri_template_struct() {
+    let file = UserFile {
+        user: "alice".into(),
+        path: "a/b c".into(),
+    };
+    assert_eq!(file.expand(), "/users/alice/files/a/b%20c");
+    assert_eq!(
+        UserFile::parse("/users/alice/files/a/b%20c").unwrap().path,
+        "a/b%20c"
+    );
+    assert_eq!(UserFile::parse("/groups/alice"), None);
+    assert_eq!(
+        UserFile::template().to_string(),
+        "/users/{user}/files/{+path}"
+    );
+}
+
+#[test]
+fn uri_template_struct_field_names() {
+    let search = Search {
+        scope: vec!["a".into(), "b".into()],
+        q: Some("x y".into()),
+        r#type: Some("all".into()),
+        page_size: Some("10".into()),
+    };
+    let uri = search.expand();
+    assert_eq!(uri, "/search/a/b?q=x%20y&type=all&page.size=10");
+    assert_eq!(Search::parse(&uri).unwrap(), search);
+    assert_eq!(Search::parse("/search").unwrap(), Search::default());
+}
+
+uri_template!(Items = "/items{?q,page}{&filters*}");
+uri_template!(Filters = "/filters{?filters*}");
+
+#[test]
+fn uri_template_struct_round_trip() {
+    for uri in [
+        "/items",
+        "/items?q=x",
+        "/items?page=2",
+        "/items?q=x&page=2&a=1&b=2",
+        "/items?q=&page=2",
+    ] {
+        assert_eq!(Items::parse(uri).unwrap().expand(), uri);
+    }
+    let items = Items::parse("/items?q=x&a=1&b=2").unwrap();
+    assert_eq!(items.q.as_deref(), Some("x"));
+    assert_eq!(items.page, None);
+    assert_eq!(
+        items.filters,
+        [
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string())
+        ]
+    );
+}